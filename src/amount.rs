@@ -0,0 +1,203 @@
+//! `amount` money as handled by the api
+
+use std::{
+    fmt::Display,
+    iter::Sum,
+    ops::{Add, AddAssign, Mul, Sub, SubAssign},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// An amount of money, in cents
+///
+/// The helloasso api express every amount as an integer number of cents,
+/// `1250` is `12,50 €`.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Amount(u64);
+
+impl Amount {
+    /// An amount of `0 €`
+    pub const ZERO: Amount = Amount(0);
+
+    /// Create an amount from a number of cents
+    pub const fn from_cents(cents: u64) -> Self {
+        Amount(cents)
+    }
+
+    /// Create an amount from a whole number of euros, saturating at the largest amount
+    pub const fn from_euros(euros: u64) -> Self {
+        Amount(euros.saturating_mul(100))
+    }
+
+    /// Create an amount from a whole number of euros, `None` when it overflows
+    pub const fn checked_from_euros(euros: u64) -> Option<Self> {
+        match euros.checked_mul(100) {
+            Some(cents) => Some(Amount(cents)),
+            None => None,
+        }
+    }
+
+    /// Get the amount as a number of cents
+    pub const fn cents(&self) -> u64 {
+        self.0
+    }
+
+    /// Add an amount, `None` when it overflows
+    pub const fn checked_add(self, rhs: Amount) -> Option<Amount> {
+        match self.0.checked_add(rhs.0) {
+            Some(cents) => Some(Amount(cents)),
+            None => None,
+        }
+    }
+
+    /// Multiply by a quantity, `None` when it overflows
+    pub const fn checked_mul(self, quantity: u64) -> Option<Amount> {
+        match self.0.checked_mul(quantity) {
+            Some(cents) => Some(Amount(cents)),
+            None => None,
+        }
+    }
+
+    /// Subtract an amount, `None` when `rhs` is greater than this amount
+    pub const fn checked_sub(self, rhs: Amount) -> Option<Amount> {
+        match self.0.checked_sub(rhs.0) {
+            Some(cents) => Some(Amount(cents)),
+            None => None,
+        }
+    }
+}
+
+/// Display the amount in euros the french way, ie `12,50 €`
+impl Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{},{:02} €", self.0 / 100, self.0 % 100)
+    }
+}
+
+/// Saturates at the largest amount, see `checked_add`
+impl Add for Amount {
+    type Output = Amount;
+
+    fn add(self, rhs: Amount) -> Self::Output {
+        Amount(self.0.saturating_add(rhs.0))
+    }
+}
+
+impl AddAssign for Amount {
+    fn add_assign(&mut self, rhs: Amount) {
+        *self = *self + rhs;
+    }
+}
+
+/// Panics when `rhs` is greater than `self`, even in release builds, see `checked_sub`
+impl Sub for Amount {
+    type Output = Amount;
+
+    fn sub(self, rhs: Amount) -> Self::Output {
+        self.checked_sub(rhs)
+            .expect("Can't subtract an amount greater than the amount")
+    }
+}
+
+impl SubAssign for Amount {
+    fn sub_assign(&mut self, rhs: Amount) {
+        *self = *self - rhs;
+    }
+}
+
+/// The amount of several identical items, saturating at the largest amount, see `checked_mul`
+impl Mul<u64> for Amount {
+    type Output = Amount;
+
+    fn mul(self, quantity: u64) -> Self::Output {
+        Amount(self.0.saturating_mul(quantity))
+    }
+}
+
+/// Saturates at the largest amount, like `Add`
+impl Sum for Amount {
+    fn sum<I: Iterator<Item = Amount>>(iter: I) -> Self {
+        iter.fold(Amount::ZERO, Add::add)
+    }
+}
+
+impl<'a> Sum<&'a Amount> for Amount {
+    fn sum<I: Iterator<Item = &'a Amount>>(iter: I) -> Self {
+        iter.copied().sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Amount;
+
+    #[test]
+    fn sum_amounts() {
+        let amounts = [Amount::from_cents(1250), Amount::from_cents(750)];
+
+        assert_eq!(amounts.iter().sum::<Amount>(), Amount::from_cents(2000));
+    }
+
+    #[test]
+    fn amounts_arithmetic() {
+        assert_eq!(Amount::from_euros(12), Amount::from_cents(1200));
+        assert_eq!(
+            Amount::from_euros(12) - Amount::from_cents(50),
+            Amount::from_cents(1150)
+        );
+        assert_eq!(Amount::from_cents(250) * 3, Amount::from_cents(750));
+        assert_eq!(Amount::ZERO.checked_sub(Amount::from_cents(1)), None);
+    }
+
+    #[test]
+    fn amounts_overflow() {
+        let max = Amount::from_cents(u64::MAX);
+
+        assert_eq!(max + Amount::from_cents(1), max);
+        assert_eq!(max * 2, max);
+        assert_eq!([max, max].iter().sum::<Amount>(), max);
+        assert_eq!(Amount::from_euros(u64::MAX), max);
+
+        assert_eq!(max.checked_add(Amount::from_cents(1)), None);
+        assert_eq!(max.checked_mul(2), None);
+        assert_eq!(Amount::checked_from_euros(u64::MAX), None);
+        assert_eq!(
+            Amount::checked_from_euros(12),
+            Some(Amount::from_cents(1200))
+        );
+        assert_eq!(
+            Amount::from_cents(250).checked_mul(3),
+            Some(Amount::from_cents(750))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "greater than the amount")]
+    fn sub_underflow() {
+        let _ = Amount::from_cents(1) - Amount::from_cents(2);
+    }
+
+    #[test]
+    #[should_panic(expected = "greater than the amount")]
+    fn sub_assign_underflow() {
+        let mut amount = Amount::ZERO;
+        amount -= Amount::from_cents(1);
+    }
+
+    #[test]
+    fn display_in_euros() {
+        assert_eq!(Amount::from_cents(1250).to_string(), "12,50 €");
+        assert_eq!(Amount::from_cents(5).to_string(), "0,05 €");
+        assert_eq!(Amount::from_euros(30).to_string(), "30,00 €");
+    }
+
+    #[test]
+    fn deserialize_from_cents() {
+        let amount: Amount = serde_json::from_str("1250").unwrap();
+
+        assert_eq!(amount.cents(), 1250);
+    }
+}
@@ -0,0 +1,488 @@
+//! `blocking` a synchronous client, for scripts and command line tools
+//!
+//! The blocking client sends its requests with `reqwest::blocking` and runs the
+//! [async client](crate::HelloAsso) logic on the calling thread, no async runtime is
+//! needed. Like `reqwest::blocking`, it must not be used from within an async runtime:
+//! reqwest panics when the client is created, used or dropped there.
+//!
+//! ```rust,no_run
+//! # fn main() -> helloasso::Result<()> {
+//! let client = helloasso::blocking::HelloAsso::from_env()?;
+//! let organization = client.get_organization("my-asso")?;
+//! println!("{}", organization.name);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::{
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use url::Url;
+
+use crate::{
+    client::{HelloAssoBuilder, DEFAULT_ENVIRONMENT},
+    runtime::block_on,
+    transport::BlockingTransport,
+    AuthorizedOrg, CheckoutIntent, CheckoutIntentRequest, DirectoryFormsQuery, DirectoryOrgQuery,
+    Error, FormLightModel, FormPublic, FormType, FormsFilters, Item, ItemFilters, NotificationType,
+    NotificationUrl, Order, OrderCancellation, OrderFilters, Organization, OrganizationLegalInfo,
+    OrganizationProfile, OrganizationToken, Paginated, PaginationParams, Participant, Partner,
+    Payment, PaymentFilters, Pkce, QuickCreateForm, QuickCreatedForm, RefundOperation,
+    RefundOptions, ResponseMeta, Result, Session, StoredToken, TagStatistics,
+};
+
+/// Wrap async methods of the client into blocking ones
+macro_rules! blocking {
+    ($($(#[$doc:meta])* fn $name:ident(&self $(, $arg:ident: $ty:ty)*) -> $ret:ty;)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $name(&self $(, $arg: $ty)*) -> $ret {
+                block_on(self.inner.$name($($arg),*))
+            }
+        )*
+    };
+}
+
+/// A blocking client, see the [async client](crate::HelloAsso) for the documentation of each method
+///
+/// Every constructor and method panics when called from within an async runtime,
+/// see the [module](crate::blocking) documentation.
+#[derive(Debug, Clone)]
+pub struct HelloAsso {
+    inner: crate::HelloAsso,
+}
+
+impl HelloAsso {
+    /// Create a new client to interact with the api
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn new(client_id: String, client_secret: String) -> Result<Self> {
+        HelloAsso::from_builder(crate::HelloAsso::builder(client_id, client_secret))
+    }
+
+    /// Create a new client from the environment variables, see [from_env](crate::HelloAsso::from_env)
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn from_env() -> Result<Self> {
+        HelloAsso::from_builder(crate::HelloAsso::env_builder()?)
+    }
+
+    /// Restore a client from a session, see [from_session](crate::HelloAsso::from_session)
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn from_session(client_id: String, session: Session) -> Result<Self> {
+        HelloAsso::from_builder(crate::HelloAsso::session_builder(client_id, session)?)
+    }
+
+    /// Build a client with the options of a [builder](crate::HelloAsso::builder)
+    ///
+    /// The tokens are fetched unless they were set with `tokens`. The requests are sent
+    /// with a `reqwest::blocking` client built with the options of the builder, unless
+    /// a transport is set. A client set with `http_client` is ignored.
+    /// Panics when called from within an async runtime.
+    pub fn from_builder(mut builder: HelloAssoBuilder) -> Result<Self> {
+        builder.blocking_transport()?;
+        if builder.stored_token().access_token.is_empty() {
+            block_on(builder.get_token())?;
+        }
+        let inner = builder.config_client()?.build();
+
+        Ok(HelloAsso { inner })
+    }
+
+    /// Use an async client, ie built with the [builder](crate::HelloAsso::builder), from blocking code
+    ///
+    /// Both clients share their tokens. The requests of the blocking client are sent with
+    /// a default `reqwest::blocking` client, the http options and transport of the async
+    /// client aren't used, see `from_builder` to configure them.
+    /// Panics when called from within an async runtime.
+    pub fn from_async(client: crate::HelloAsso) -> Result<Self> {
+        let transport = reqwest::blocking::Client::builder()
+            .build()
+            .map_err(Error::ReqwestErr)?;
+
+        Ok(HelloAsso {
+            inner: client.with_transport(Arc::new(BlockingTransport(transport))),
+        })
+    }
+
+    /// The async client used under the hood
+    ///
+    /// It shares the transport of the blocking client, its requests block the thread.
+    pub fn as_async(&self) -> &crate::HelloAsso {
+        &self.inner
+    }
+
+    /// Check that the api can be reached and return the round trip time
+    ///
+    /// Panics when called from within an async runtime.
+    pub fn ping(base_url: Option<&str>) -> Result<Duration> {
+        let url = Url::from_str(base_url.unwrap_or(DEFAULT_ENVIRONMENT.api_url()))?;
+
+        let start = Instant::now();
+        reqwest::blocking::Client::builder()
+            .build()?
+            .head(url)
+            .send()?;
+
+        Ok(start.elapsed())
+    }
+
+    /// Refresh the access_token of the client
+    pub fn refresh_token(&self) -> Result<&Self> {
+        block_on(self.inner.refresh_token())?;
+        Ok(self)
+    }
+
+    pub fn stored_token(&self) -> StoredToken {
+        self.inner.stored_token()
+    }
+
+    pub fn session(&self) -> Session {
+        self.inner.session()
+    }
+
+    pub fn flush(&self) -> Result<()> {
+        block_on(self.inner.flush())
+    }
+
+    pub fn needs_refresh(&self) -> bool {
+        self.inner.needs_refresh()
+    }
+
+    pub fn token_expires_at(&self) -> DateTime<Utc> {
+        self.inner.token_expires_at()
+    }
+
+    pub fn token_expires_in(&self) -> Duration {
+        self.inner.token_expires_in()
+    }
+
+    pub fn is_token_valid(&self, margin: Duration) -> bool {
+        self.inner.is_token_valid(margin)
+    }
+
+    pub fn authorize_url(&self, redirect_uri: &str, state: &str, pkce: &Pkce) -> Url {
+        self.inner.authorize_url(redirect_uri, state, pkce)
+    }
+
+    /// A client acting on behalf of an organization, see [with_organization_token](crate::HelloAsso::with_organization_token)
+    pub fn with_organization_token(
+        &self,
+        token: OrganizationToken,
+        on_token_rotated: impl Fn(&OrganizationToken) + Send + Sync + 'static,
+    ) -> Self {
+        HelloAsso {
+            inner: self.inner.with_organization_token(token, on_token_rotated),
+        }
+    }
+
+    blocking! {
+        fn get_raw(&self, path: &str, query: &[(&str, &str)]) -> Result<Value>;
+        fn post_raw(&self, path: &str, query: &[(&str, &str)], body: &Value) -> Result<Value>;
+
+        fn exchange_authorization_code(
+            &self,
+            code: &str,
+            redirect_uri: &str,
+            pkce: &Pkce
+        ) -> Result<OrganizationToken>;
+
+        fn get_organization(&self, slug: &str) -> Result<Organization>;
+        fn get_organization_raw(&self, slug: &str) -> Result<Value>;
+        fn get_organizations(
+            &self,
+            slugs: &[&str],
+            concurrency: usize
+        ) -> Vec<(String, Result<Organization>)>;
+        fn get_organization_legal_info(&self, slug: &str) -> Result<OrganizationLegalInfo>;
+        fn get_organization_public_profile(&self, slug: &str) -> Result<OrganizationProfile>;
+
+        fn get_order(&self, order_id: u64) -> Result<Order>;
+        fn get_order_raw(&self, order_id: u64) -> Result<Value>;
+        fn get_order_with_meta(&self, order_id: u64) -> Result<(Order, ResponseMeta)>;
+        fn cancel_order(&self, order_id: u64) -> Result<OrderCancellation>;
+        fn list_organization_orders(
+            &self,
+            organization_slug: &str,
+            filters: &OrderFilters,
+            pagination: &PaginationParams
+        ) -> Result<Paginated<Order>>;
+        fn fetch_all_organization_orders(
+            &self,
+            organization_slug: &str,
+            filters: &OrderFilters,
+            max_in_flight: usize
+        ) -> Result<Vec<Order>>;
+        fn list_form_orders(
+            &self,
+            organization_slug: &str,
+            form_type: FormType,
+            form_slug: &str,
+            filters: &OrderFilters,
+            pagination: &PaginationParams
+        ) -> Result<Paginated<Order>>;
+        fn fetch_all_form_orders(
+            &self,
+            organization_slug: &str,
+            form_type: FormType,
+            form_slug: &str,
+            filters: &OrderFilters,
+            max_in_flight: usize
+        ) -> Result<Vec<Order>>;
+
+        fn get_item(&self, item_id: u64, with_details: bool) -> Result<Item>;
+        fn get_item_raw(&self, item_id: u64, with_details: bool) -> Result<Value>;
+        fn list_organization_items(
+            &self,
+            organization_slug: &str,
+            filters: &ItemFilters,
+            pagination: &PaginationParams
+        ) -> Result<Paginated<Item>>;
+        fn fetch_all_organization_items(
+            &self,
+            organization_slug: &str,
+            filters: &ItemFilters,
+            max_in_flight: usize
+        ) -> Result<Vec<Item>>;
+        fn list_form_items(
+            &self,
+            organization_slug: &str,
+            form_type: FormType,
+            form_slug: &str,
+            filters: &ItemFilters,
+            pagination: &PaginationParams
+        ) -> Result<Paginated<Item>>;
+        fn fetch_all_form_items(
+            &self,
+            organization_slug: &str,
+            form_type: FormType,
+            form_slug: &str,
+            filters: &ItemFilters,
+            max_in_flight: usize
+        ) -> Result<Vec<Item>>;
+
+        fn get_payment(&self, payment_id: u64) -> Result<Payment>;
+        fn get_payment_raw(&self, payment_id: u64) -> Result<Value>;
+        fn get_payment_with_meta(&self, payment_id: u64) -> Result<(Payment, ResponseMeta)>;
+        fn get_payment_with_order(&self, payment_id: u64) -> Result<(Payment, Option<Order>)>;
+        fn list_organization_payments(
+            &self,
+            organization_slug: &str,
+            filters: &PaymentFilters,
+            pagination: &PaginationParams
+        ) -> Result<Paginated<Payment>>;
+        fn fetch_all_organization_payments(
+            &self,
+            organization_slug: &str,
+            filters: &PaymentFilters,
+            max_in_flight: usize
+        ) -> Result<Vec<Payment>>;
+        fn list_form_payments(
+            &self,
+            organization_slug: &str,
+            form_type: FormType,
+            form_slug: &str,
+            filters: &PaymentFilters,
+            pagination: &PaginationParams
+        ) -> Result<Paginated<Payment>>;
+        fn fetch_all_form_payments(
+            &self,
+            organization_slug: &str,
+            form_type: FormType,
+            form_slug: &str,
+            filters: &PaymentFilters,
+            max_in_flight: usize
+        ) -> Result<Vec<Payment>>;
+        fn refund_payment(&self, payment_id: u64, options: &RefundOptions) -> Result<RefundOperation>;
+
+        fn get_form(
+            &self,
+            organization_slug: &str,
+            form_type: FormType,
+            form_slug: &str
+        ) -> Result<FormPublic>;
+        fn get_form_raw(
+            &self,
+            organization_slug: &str,
+            form_type: FormType,
+            form_slug: &str
+        ) -> Result<Value>;
+        fn get_form_participants(
+            &self,
+            organization_slug: &str,
+            form_type: FormType,
+            form_slug: &str
+        ) -> Result<Vec<Participant>>;
+        fn list_forms(
+            &self,
+            organization_slug: &str,
+            filters: &FormsFilters,
+            pagination: &PaginationParams
+        ) -> Result<Paginated<FormLightModel>>;
+        fn fetch_all_forms(
+            &self,
+            organization_slug: &str,
+            filters: &FormsFilters,
+            max_in_flight: usize
+        ) -> Result<Vec<FormLightModel>>;
+        fn quick_create_form(
+            &self,
+            organization_slug: &str,
+            form_type: FormType,
+            form: &QuickCreateForm
+        ) -> Result<QuickCreatedForm>;
+
+        fn create_checkout_intent(
+            &self,
+            organization_slug: &str,
+            request: &CheckoutIntentRequest
+        ) -> Result<CheckoutIntent>;
+        fn get_checkout_intent(
+            &self,
+            organization_slug: &str,
+            checkout_intent_id: u64
+        ) -> Result<CheckoutIntent>;
+
+        fn directory_search_organizations(
+            &self,
+            query: &DirectoryOrgQuery,
+            pagination: &PaginationParams
+        ) -> Result<Paginated<Organization>>;
+        fn directory_search_forms(
+            &self,
+            query: &DirectoryFormsQuery,
+            pagination: &PaginationParams
+        ) -> Result<Paginated<FormLightModel>>;
+
+        fn my_organizations(&self) -> Result<Vec<Organization>>;
+        fn get_authorized_organizations(&self) -> Result<Vec<AuthorizedOrg>>;
+
+        fn get_partner(&self) -> Result<Partner>;
+        fn set_notification_url(
+            &self,
+            notification_url: &str,
+            notification_type: Option<NotificationType>
+        ) -> Result<NotificationUrl>;
+        fn remove_notification_url(
+            &self,
+            notification_type: Option<NotificationType>
+        ) -> Result<()>;
+        fn set_organization_notification_url(
+            &self,
+            organization_slug: &str,
+            notification_url: &str,
+            notification_type: Option<NotificationType>
+        ) -> Result<NotificationUrl>;
+        fn remove_organization_notification_url(
+            &self,
+            organization_slug: &str,
+            notification_type: Option<NotificationType>
+        ) -> Result<()>;
+
+        fn get_tag(&self, tag_name: &str) -> Result<TagStatistics>;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::HelloAsso;
+    use crate::{
+        test_util::{MockApi, CLIENT_ID, CLIENT_SECRET, ORGANIZATION_SLUG, PAYMENT_ID},
+        Error, OrganizationToken, Pkce, StoredToken,
+    };
+
+    #[tokio::test]
+    async fn blocking_client() {
+        let api = MockApi::start().await;
+        let uri = api.uri();
+
+        // Outside of the runtime, like in a program without one
+        let (payment, refreshed) = std::thread::spawn(move || {
+            let builder =
+                crate::HelloAsso::builder(CLIENT_ID.to_string(), CLIENT_SECRET.to_string())
+                    .with_base_url(&uri)
+                    .unwrap();
+            let client = HelloAsso::from_builder(builder).unwrap();
+            let expires_at = client.stored_token().expires_at;
+
+            let payment = client.get_payment(PAYMENT_ID).unwrap();
+            client.refresh_token().unwrap();
+            (payment, client.stored_token().expires_at > expires_at)
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(payment.id, PAYMENT_ID);
+        assert!(refreshed);
+    }
+
+    #[tokio::test]
+    async fn blocking_not_found() {
+        let api = MockApi::start().await;
+        let uri = api.uri();
+
+        let organization = tokio::task::spawn_blocking(move || {
+            let builder =
+                crate::HelloAsso::builder(CLIENT_ID.to_string(), CLIENT_SECRET.to_string())
+                    .with_base_url(&uri)
+                    .unwrap();
+            let client = HelloAsso::from_builder(builder).unwrap();
+
+            assert!(client.get_organization(ORGANIZATION_SLUG).is_ok());
+            client.get_organization("unknown-asso")
+        })
+        .await
+        .unwrap();
+
+        assert!(
+            matches!(&organization, Err(Error::NotFound { resource: "organization", id, .. }) if id == "unknown-asso"),
+            "{organization:?}"
+        );
+    }
+
+    #[test]
+    fn blocking_request() {
+        let client = crate::HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_url("http://127.0.0.1:1/v5", "http://127.0.0.1:1/oauth2/token")
+            .unwrap()
+            .build();
+        let client = HelloAsso::from_async(client).unwrap();
+
+        let organization = client.get_organization("my-asso");
+
+        assert!(matches!(organization, Err(Error::ReqwestErr(_))));
+    }
+
+    #[test]
+    fn organization_client() {
+        let client = HelloAsso::from_async(
+            crate::HelloAsso::builder("abc".to_string(), "abc".to_string()).build(),
+        )
+        .unwrap();
+        let token = StoredToken {
+            access_token: "access".into(),
+            refresh_token: "refresh".into(),
+            expires_at: Utc::now(),
+        };
+
+        let url = client.authorize_url("https://example.com/callback", "xyz", &Pkce::new());
+        let organization_client = client.with_organization_token(
+            OrganizationToken {
+                organization_slug: "other-asso".to_string(),
+                token: token.clone(),
+            },
+            |_| {},
+        );
+
+        assert_eq!(url.path(), "/authorize");
+        assert_eq!(organization_client.stored_token(), token);
+    }
+}
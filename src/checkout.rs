@@ -0,0 +1,557 @@
+//! `checkout` the checkout intents, used to collect a payment on helloasso
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use url::Url;
+
+use crate::{Amount, Error, HelloAsso, Order, Result};
+
+/// The body of a checkout intent creation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckoutIntentRequest {
+    /// Amount of the whole checkout, the initial amount plus every term
+    pub total_amount: Amount,
+    /// Amount paid right away
+    pub initial_amount: Amount,
+    pub item_name: String,
+    pub back_url: String,
+    pub error_url: String,
+    pub return_url: String,
+    pub contains_donation: bool,
+    /// Installments paid after the initial amount
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub terms: Vec<CheckoutTerm>,
+    /// Prefills the payer information on the checkout page
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payer: Option<CheckoutPayer>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
+}
+
+impl CheckoutIntentRequest {
+    /// Start building a checkout intent request
+    ///
+    /// ```rust
+    /// # use helloasso::{Amount, CheckoutIntentRequest};
+    /// let request = CheckoutIntentRequest::builder("Adhésion 2023", Amount::from_cents(3000))
+    ///     .urls(
+    ///         "https://example.com/back",
+    ///         "https://example.com/error",
+    ///         "https://example.com/return",
+    ///     )
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(request.initial_amount, request.total_amount);
+    /// ```
+    pub fn builder(item_name: impl Into<String>, total_amount: Amount) -> CheckoutIntentBuilder {
+        CheckoutIntentBuilder {
+            item_name: item_name.into(),
+            total_amount,
+            initial_amount: None,
+            back_url: None,
+            error_url: None,
+            return_url: None,
+            contains_donation: false,
+            terms: Vec::new(),
+            payer: None,
+            metadata: None,
+        }
+    }
+
+    /// Check the amounts and urls of the checkout
+    ///
+    /// The total amount and the terms must be greater than zero, the initial amount
+    /// can't exceed the total amount and the initial amount plus the terms must be
+    /// equal to the total amount. The urls must be https urls, and the metadata
+    /// must be valid, see [Metadata](crate::Metadata).
+    pub fn validate(&self) -> Result<()> {
+        if self.total_amount == Amount::ZERO {
+            return Err(Error::InvalidInput(
+                "total amount must be greater than zero".to_string(),
+            ));
+        }
+        if self.terms.iter().any(|term| term.amount == Amount::ZERO) {
+            return Err(Error::InvalidInput(
+                "terms amount must be greater than zero".to_string(),
+            ));
+        }
+
+        if self.initial_amount > self.total_amount {
+            return Err(Error::InvalidInput(format!(
+                "initial amount ({}) is greater than total amount ({})",
+                self.initial_amount, self.total_amount
+            )));
+        }
+
+        let terms = self
+            .terms
+            .iter()
+            .try_fold(Amount::ZERO, |sum, term| sum.checked_add(term.amount))
+            .ok_or_else(|| Error::InvalidInput("terms amount overflow".to_string()))?;
+        if self.initial_amount.checked_add(terms) != Some(self.total_amount) {
+            return Err(Error::InvalidInput(format!(
+                "initial amount ({}) plus terms ({}) must be equal to total amount ({})",
+                self.initial_amount, terms, self.total_amount
+            )));
+        }
+
+        check_https("back", &self.back_url)?;
+        check_https("error", &self.error_url)?;
+        check_https("return", &self.return_url)?;
+
+        // The metadata may come from a checkout intent answered by the api
+        match &self.metadata {
+            Some(metadata) => metadata.validate(),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Check that an url of a checkout intent is an https url
+fn check_https(name: &str, url: &str) -> Result<()> {
+    match Url::parse(url) {
+        Ok(parsed) if parsed.scheme() == "https" => Ok(()),
+        _ => Err(Error::InvalidInput(format!(
+            "the {name} url must be an https url, got {url:?}"
+        ))),
+    }
+}
+
+/// A builder for [CheckoutIntentRequest](crate::CheckoutIntentRequest)
+///
+/// The initial amount defaults to the total amount, the urls are required.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CheckoutIntentBuilder {
+    item_name: String,
+    total_amount: Amount,
+    initial_amount: Option<Amount>,
+    back_url: Option<String>,
+    error_url: Option<String>,
+    return_url: Option<String>,
+    contains_donation: bool,
+    terms: Vec<CheckoutTerm>,
+    payer: Option<CheckoutPayer>,
+    metadata: Option<Metadata>,
+}
+
+impl CheckoutIntentBuilder {
+    /// Amount paid right away, the rest is paid by the terms
+    pub fn initial_amount(&mut self, amount: Amount) -> &mut Self {
+        self.initial_amount = Some(amount);
+        self
+    }
+
+    /// Set the url used when the payer leaves, on error and once the payment is done
+    pub fn urls(
+        &mut self,
+        back_url: impl Into<String>,
+        error_url: impl Into<String>,
+        return_url: impl Into<String>,
+    ) -> &mut Self {
+        self.back_url = Some(back_url.into());
+        self.error_url = Some(error_url.into());
+        self.return_url = Some(return_url.into());
+        self
+    }
+
+    pub fn contains_donation(&mut self, contains_donation: bool) -> &mut Self {
+        self.contains_donation = contains_donation;
+        self
+    }
+
+    /// Add an installment paid after the initial amount
+    pub fn term(&mut self, amount: Amount, date: NaiveDate) -> &mut Self {
+        self.terms.push(CheckoutTerm { amount, date });
+        self
+    }
+
+    pub fn payer(&mut self, payer: CheckoutPayer) -> &mut Self {
+        self.payer = Some(payer);
+        self
+    }
+
+    pub fn metadata(&mut self, metadata: Metadata) -> &mut Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Build the request, the urls must be set and the amounts valid
+    pub fn build(&self) -> Result<CheckoutIntentRequest> {
+        let (Some(back_url), Some(error_url), Some(return_url)) = (
+            self.back_url.clone(),
+            self.error_url.clone(),
+            self.return_url.clone(),
+        ) else {
+            return Err(Error::InvalidInput(
+                "the back, error and return urls of a checkout intent are required".to_string(),
+            ));
+        };
+
+        let request = CheckoutIntentRequest {
+            total_amount: self.total_amount,
+            initial_amount: self.initial_amount.unwrap_or(self.total_amount),
+            item_name: self.item_name.clone(),
+            back_url,
+            error_url,
+            return_url,
+            contains_donation: self.contains_donation,
+            terms: self.terms.clone(),
+            payer: self.payer.clone(),
+            metadata: self.metadata.clone(),
+        };
+        request.validate()?;
+
+        Ok(request)
+    }
+}
+
+/// The payer information prefilled on the checkout page
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckoutPayer {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub zip_code: Option<String>,
+    /// Country code, ie `FRA`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub country: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date_of_birth: Option<NaiveDate>,
+    /// The company paying, when the payer pays on behalf of a company
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub company_name: Option<String>,
+}
+
+/// Maximum size of the serialized metadata of a checkout intent, in bytes
+pub const METADATA_MAX_SIZE: usize = 20_000;
+
+/// Metadata attached to a checkout intent, given back in the order and notifications
+///
+/// It must be a json object whose serialized size is at most [METADATA_MAX_SIZE](crate::METADATA_MAX_SIZE).
+/// This is only checked on the requests, the metadata of a checkout intent answered
+/// by the api is kept as is, even when it was set by another client.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Metadata(Value);
+
+impl Metadata {
+    /// Create metadata from any serializable value, checking its kind and size
+    pub fn try_new(value: impl Serialize) -> Result<Self> {
+        let value = serde_json::to_value(value).map_err(|err| {
+            Error::InvalidInput(format!("metadata can't be serialized to json: {err}"))
+        })?;
+
+        Metadata::try_from(value)
+    }
+
+    pub fn as_value(&self) -> &Value {
+        &self.0
+    }
+
+    /// Check the kind and size of the metadata
+    pub fn validate(&self) -> Result<()> {
+        if !self.0.is_object() {
+            return Err(Error::InvalidInput(
+                "metadata must be a json object".to_string(),
+            ));
+        }
+
+        let size = self.0.to_string().len();
+        if size > METADATA_MAX_SIZE {
+            return Err(Error::InvalidInput(format!(
+                "metadata is {size} bytes, the maximum is {METADATA_MAX_SIZE} bytes"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<Value> for Metadata {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self> {
+        let metadata = Metadata(value);
+        metadata.validate()?;
+        Ok(metadata)
+    }
+}
+
+impl From<Metadata> for Value {
+    fn from(metadata: Metadata) -> Self {
+        metadata.0
+    }
+}
+
+/// An installment of a checkout
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckoutTerm {
+    pub amount: Amount,
+    /// Date of the installment
+    pub date: NaiveDate,
+}
+
+/// A checkout intent, the payer must be redirected to `redirect_url`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckoutIntent {
+    pub id: u64,
+    pub redirect_url: Option<String>,
+    pub metadata: Option<Metadata>,
+    /// The order created once the checkout is paid
+    pub order: Option<Order>,
+}
+
+impl HelloAsso {
+    /// Create a checkout intent
+    ///
+    /// The request amounts are checked before sending, see [validate](crate::CheckoutIntentRequest::validate).
+    pub async fn create_checkout_intent(
+        &self,
+        organization_slug: &str,
+        request: &CheckoutIntentRequest,
+    ) -> Result<CheckoutIntent> {
+        request.validate()?;
+
+        let url = self.endpoint(&["organizations", organization_slug, "checkout-intents"]);
+        self.post(url, &(), request)
+            .await
+            .map_err(|err| err.with_resource("organization", organization_slug))
+    }
+
+    /// Get a checkout intent, its order is set once the payer paid
+    pub async fn get_checkout_intent(
+        &self,
+        organization_slug: &str,
+        checkout_intent_id: u64,
+    ) -> Result<CheckoutIntent> {
+        let url = self.endpoint(&[
+            "organizations",
+            organization_slug,
+            "checkout-intents",
+            &checkout_intent_id.to_string(),
+        ]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("checkout intent", checkout_intent_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_util::MockApi, Amount, CheckoutIntent, CheckoutIntentRequest, CheckoutPayer,
+        CheckoutTerm, Error, Metadata, METADATA_MAX_SIZE,
+    };
+    use chrono::NaiveDate;
+    use serde_json::json;
+
+    fn december() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2023, 12, 1).unwrap()
+    }
+
+    fn request(total: u64, initial: u64, terms: &[u64]) -> CheckoutIntentRequest {
+        CheckoutIntentRequest {
+            total_amount: Amount::from_cents(total),
+            initial_amount: Amount::from_cents(initial),
+            item_name: "Adhésion".to_string(),
+            back_url: "https://example.com/back".to_string(),
+            error_url: "https://example.com/error".to_string(),
+            return_url: "https://example.com/return".to_string(),
+            contains_donation: false,
+            terms: terms
+                .iter()
+                .map(|amount| CheckoutTerm {
+                    amount: Amount::from_cents(*amount),
+                    date: december(),
+                })
+                .collect(),
+            payer: None,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn valid_amounts() {
+        assert!(request(3000, 3000, &[]).validate().is_ok());
+        assert!(request(3000, 1000, &[1000, 1000]).validate().is_ok());
+    }
+
+    #[test]
+    fn initial_amount_greater_than_total() {
+        let result = request(1000, 3000, &[]).validate();
+
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn terms_not_matching_total() {
+        assert!(matches!(
+            request(3000, 1000, &[1000]).validate(),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            request(3000, 1000, &[]).validate(),
+            Err(Error::InvalidInput(_))
+        ));
+        // Saturating sums would match the total
+        assert!(matches!(
+            request(u64::MAX, 1000, &[u64::MAX, 1]).validate(),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            request(u64::MAX, u64::MAX, &[1]).validate(),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn builder_with_terms() {
+        let built = CheckoutIntentRequest::builder("Adhésion", Amount::from_cents(3000))
+            .initial_amount(Amount::from_cents(1000))
+            .term(Amount::from_cents(2000), december())
+            .urls(
+                "https://example.com/back",
+                "https://example.com/error",
+                "https://example.com/return",
+            )
+            .payer(CheckoutPayer {
+                email: Some("jane@example.com".to_string()),
+                ..Default::default()
+            })
+            .build()
+            .unwrap();
+
+        let mut expected = request(3000, 1000, &[2000]);
+        expected.payer = built.payer.clone();
+        assert_eq!(built, expected);
+        assert_eq!(
+            serde_json::to_value(&built).unwrap()["payer"],
+            json!({ "email": "jane@example.com" })
+        );
+        assert_eq!(
+            serde_json::to_value(&built).unwrap()["terms"],
+            json!([{ "amount": 2000, "date": "2023-12-01" }])
+        );
+    }
+
+    #[test]
+    fn amounts_greater_than_zero() {
+        assert!(matches!(
+            request(0, 0, &[]).validate(),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            request(3000, 3000, &[0]).validate(),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn urls_must_be_https() {
+        let mut insecure = request(3000, 3000, &[]);
+        insecure.return_url = "http://example.com/return".to_string();
+        assert!(matches!(insecure.validate(), Err(Error::InvalidInput(_))));
+
+        let mut invalid = request(3000, 3000, &[]);
+        invalid.back_url = "not an url".to_string();
+        assert!(matches!(invalid.validate(), Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn builder_requires_urls() {
+        let result = CheckoutIntentRequest::builder("Adhésion", Amount::from_cents(3000)).build();
+
+        assert!(matches!(result, Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn deserialize_paid_checkout_intent() {
+        let intent: CheckoutIntent = serde_json::from_value(json!({
+            "id": 7,
+            "metadata": { "userId": 42 },
+            "order": { "id": 12, "items": [] }
+        }))
+        .unwrap();
+
+        assert_eq!(intent.order.map(|order| order.id), Some(12));
+        assert_eq!(
+            intent.metadata.map(serde_json::Value::from),
+            Some(json!({ "userId": 42 }))
+        );
+    }
+
+    #[test]
+    fn valid_metadata() {
+        let metadata = Metadata::try_new(json!({ "userId": 42 })).unwrap();
+
+        assert_eq!(metadata.as_value(), &json!({ "userId": 42 }));
+    }
+
+    #[test]
+    fn metadata_must_be_an_object() {
+        assert!(matches!(
+            Metadata::try_new(vec![1, 2, 3]),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn decode_unchecked_metadata() {
+        let blob = "a".repeat(METADATA_MAX_SIZE);
+        let intent: CheckoutIntent = serde_json::from_value(json!({
+            "id": 5,
+            "metadata": { "blob": blob }
+        }))
+        .unwrap();
+        let metadata = intent.metadata.unwrap();
+        assert!(metadata.validate().is_err());
+
+        let mut request = CheckoutIntentRequest::builder("Adhésion", Amount::from_cents(3000))
+            .urls(
+                "https://example.com/back",
+                "https://example.com/error",
+                "https://example.com/return",
+            )
+            .build()
+            .unwrap();
+        request.metadata = Some(metadata);
+        assert!(matches!(request.validate(), Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn metadata_too_big() {
+        let blob = "a".repeat(METADATA_MAX_SIZE);
+
+        assert!(matches!(
+            Metadata::try_new(json!({ "blob": blob })),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn checkout_intent_unknown_organization() {
+        let api = MockApi::start().await;
+        let client = api.client().await.unwrap();
+
+        let intent = client
+            .create_checkout_intent("unknown-asso", &request(3000, 3000, &[]))
+            .await;
+        assert!(
+            matches!(&intent, Err(Error::NotFound { resource: "organization", id, .. }) if id == "unknown-asso"),
+            "{intent:?}"
+        );
+    }
+}
@@ -2,40 +2,166 @@
 
 use std::{
     collections::HashMap,
+    env,
     str::FromStr,
-    time::{Duration, SystemTime},
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
 };
 
+use chrono::{DateTime, Utc};
 use derivative::Derivative;
-#[cfg(feature = "log")]
-use log::{error, info};
-use reqwest::{header, StatusCode};
-use serde::Deserialize;
+use futures_util::lock::Mutex;
+use reqwest::{
+    header::{self, HeaderValue},
+    RequestBuilder, Response, StatusCode,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
 use url::Url;
 
-use crate::{error::Error, AuthenticationError};
+use crate::{
+    decode::{decode_json, DecodeMode},
+    error::Error,
+    interceptor::{Interceptor, Interceptors},
+    logging::{log_error, log_info, log_warn, record_response},
+    meta::ResponseMeta,
+    rate_limit::{RateLimit, RateLimiter},
+    retry::{RetryEvent, RetryHook},
+    runtime::sleep,
+    token::{jitter, Secret, TokenRotatedHook, TokenState, REFRESH_MARGIN},
+    transport::{HttpTransport, SharedTransport},
+    ApiError, AuthenticationError, AuthorizationError, Paginated, PaginationParams, Result,
+    RetryPolicy, Session, StoredToken,
+};
+
+#[cfg(feature = "blocking")]
+use crate::transport::BlockingTransport;
 
 #[cfg(not(test))]
-const URL: &str = "https://api.helloasso.com/v5";
-#[cfg(not(test))]
-const OAUTH2_TOKEN_URL: &str = "https://api.helloasso.com/oauth2/token";
+pub(crate) const DEFAULT_ENVIRONMENT: Environment = Environment::Production;
 #[cfg(test)]
-const URL: &str = "https://api.helloasso-sandbox.com/v5";
-#[cfg(test)]
-const OAUTH2_TOKEN_URL: &str = "https://api.helloasso-sandbox.com/oauth2/token";
+pub(crate) const DEFAULT_ENVIRONMENT: Environment = Environment::Sandbox;
+
+/// The helloasso platform the client talks to
+///
+/// The sandbox is a separate platform with its own accounts, meant for testing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Environment {
+    #[default]
+    Production,
+    Sandbox,
+}
+
+impl Environment {
+    /// The url of the api
+    pub fn api_url(&self) -> &'static str {
+        match self {
+            Environment::Production => "https://api.helloasso.com/v5",
+            Environment::Sandbox => "https://api.helloasso-sandbox.com/v5",
+        }
+    }
+
+    /// The url used to fetch the oauth2 tokens
+    pub fn token_url(&self) -> &'static str {
+        match self {
+            Environment::Production => "https://api.helloasso.com/oauth2/token",
+            Environment::Sandbox => "https://api.helloasso-sandbox.com/oauth2/token",
+        }
+    }
+
+    /// The page where an organization authorizes the client, see [authorize_url](crate::HelloAsso::authorize_url)
+    pub fn authorize_url(&self) -> &'static str {
+        match self {
+            Environment::Production => "https://auth.helloasso.com/authorize",
+            Environment::Sandbox => "https://auth.helloasso-sandbox.com/authorize",
+        }
+    }
+}
+
+impl FromStr for Environment {
+    type Err = Error;
+
+    /// Parse `production` or `sandbox`, whatever the case
+    fn from_str(environment: &str) -> Result<Self> {
+        match environment.trim().to_ascii_lowercase().as_str() {
+            "production" | "prod" => Ok(Environment::Production),
+            "sandbox" => Ok(Environment::Sandbox),
+            _ => Err(Error::InvalidInput(format!(
+                "unknown environment {environment}, expected production or sandbox"
+            ))),
+        }
+    }
+}
+
+/// Number of elements requested per page when fetching every page of a list
+pub(crate) const PAGE_SIZE: u32 = 100;
+
+/// Delay before retrying a failed background refresh, the least time between two refreshes
+#[cfg(feature = "tokio")]
+const AUTO_REFRESH_RETRY: Duration = Duration::from_secs(30);
 
+/// Longest sleep of the background refresh, it checks whether the client was dropped in between
+#[cfg(feature = "tokio")]
+const AUTO_REFRESH_CHECK: Duration = Duration::from_secs(300);
+
+/// A client of the helloasso api
+///
+/// The client is cheap to clone and can be shared between tasks, the clones
+/// share their tokens and a token refreshed by one is used by all of them.
+///
+/// ```rust,no_run
+/// # use helloasso::HelloAsso;
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> helloasso::Result<()> {
+/// let client = HelloAsso::new("client_id".to_string(), "client_secret".to_string()).await?;
+///
+/// let task = tokio::spawn({
+///     let client = client.clone();
+///     async move { client.get_payment(42).await }
+/// });
+/// let order = client.get_order(1).await?;
+/// let payment = task.await.unwrap()?;
+/// # Ok(())
+/// # }
+/// ```
 #[derive(Clone, Derivative)]
 #[derivative(Debug, PartialEq)]
 pub struct HelloAsso {
     pub client_id: String,
-    client_secret: String,
+    client_secret: Secret,
     pub url: Url,
     token_url: Url,
-    access_token: String,
-    refresh_token: String,
-    token_outdated_after: SystemTime,
+    authorize_url: Url,
+    /// The tokens, shared by the clones of the client
+    #[derivative(PartialEq(compare_with = "same_tokens"))]
+    tokens: Arc<RwLock<TokenState>>,
+    /// Held while refreshing, so concurrent requests refresh the token only once
+    #[derivative(PartialEq = "ignore")]
+    refresh_lock: Arc<Mutex<()>>,
+    /// How long before the expiration of the token it is refreshed
+    refresh_margin: Duration,
+    /// Maximum random delay added to the refresh margin
+    refresh_jitter: Duration,
+    retry_policy: RetryPolicy,
+    /// Shared by the clones of the client
+    #[derivative(PartialEq = "ignore")]
+    rate_limiter: Option<RateLimiter>,
+    decode_mode: DecodeMode,
+    /// Whether to authenticate again when the api refuses the access token
+    reauthenticate_on_unauthorized: bool,
+    /// Used to build the requests, they are sent with the transports
     #[derivative(PartialEq = "ignore")]
     client: reqwest::Client,
+    #[derivative(PartialEq = "ignore")]
+    transport: SharedTransport,
+    #[derivative(PartialEq = "ignore")]
+    token_transport: SharedTransport,
+    #[derivative(PartialEq = "ignore")]
+    on_token_rotated: Option<TokenRotatedHook>,
+    #[derivative(PartialEq = "ignore")]
+    on_retry: Option<RetryHook>,
+    #[derivative(PartialEq = "ignore")]
+    interceptors: Interceptors,
 }
 
 #[derive(Debug, Deserialize)]
@@ -48,26 +174,105 @@ struct RefreshToken {
 
 impl HelloAsso {
     /// Create a new client to interact with the api
-    pub async fn new(client_id: String, client_secret: String) -> Result<Self, Error> {
+    pub async fn new(client_id: String, client_secret: String) -> Result<Self> {
         let client = HelloAsso::builder(client_id, client_secret)
             .get_token()
             .await?
             .config_client()?
             .build();
 
-        #[cfg(feature = "log")]
-        info!("New client created");
+        log_info!("New client created");
+
+        Ok(client)
+    }
+
+    /// Create a new client from the environment variables
+    ///
+    /// The credentials are read from `HELLOASSO_CLIENT_ID` and `HELLOASSO_CLIENT_SECRET`.
+    /// `HELLOASSO_ENV` selects the [Environment], `production` or `sandbox`.
+    /// `HELLOASSO_BASE_URL` can be set to use another host, ie a mock of the api,
+    /// the api and token urls are derived from it.
+    pub async fn from_env() -> Result<Self> {
+        let client = HelloAsso::env_builder()?
+            .get_token()
+            .await?
+            .config_client()?
+            .build();
+
+        log_info!("New client created from environment");
+
+        Ok(client)
+    }
+
+    /// A builder with the credentials and urls of the environment variables, see `from_env`
+    pub(crate) fn env_builder() -> Result<HelloAssoBuilder> {
+        let client_id = env_var("HELLOASSO_CLIENT_ID")?;
+        let client_secret = env_var("HELLOASSO_CLIENT_SECRET")?;
+
+        let mut builder = HelloAsso::builder(client_id, client_secret);
+        if let Ok(environment) = env::var("HELLOASSO_ENV") {
+            builder.environment(environment.parse()?);
+        }
+        if let Ok(base_url) = env::var("HELLOASSO_BASE_URL") {
+            builder.base_url(&base_url)?;
+        }
+
+        Ok(builder)
+    }
+
+    /// Restore a client from a session saved with `session`
+    ///
+    /// No request is sent, an expired access token is refreshed using the refresh
+    /// token before the first request. The client secret is not needed to refresh
+    /// the tokens, so it is not part of the session. The client uses the urls of the session.
+    ///
+    /// ```rust,no_run
+    /// # use helloasso::{HelloAsso, Session};
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> helloasso::Result<()> {
+    /// # let client = HelloAsso::new("client_id".to_string(), "client_secret".to_string()).await?;
+    /// let saved = serde_json::to_string(&client.session()).unwrap();
+    ///
+    /// let session: Session = serde_json::from_str(&saved).unwrap();
+    /// let client = HelloAsso::from_session("client_id".to_string(), session)?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn from_session(client_id: String, session: Session) -> Result<Self> {
+        let client = HelloAsso::session_builder(client_id, session)?
+            .config_client()?
+            .build();
+
+        log_info!("Client restored from session");
 
         Ok(client)
     }
 
+    /// A builder with the tokens and urls of a session, see `from_session`
+    pub(crate) fn session_builder(client_id: String, session: Session) -> Result<HelloAssoBuilder> {
+        let mut builder = HelloAsso::builder(client_id, String::new());
+        if let (Some(api_url), Some(token_url)) = (&session.api_url, &session.token_url) {
+            builder.set_url(api_url.as_str(), token_url.as_str())?;
+        }
+        if let Some(authorize_url) = session.authorize_url {
+            builder.authorize_url = authorize_url;
+        }
+        builder.tokens(
+            session.token.access_token.into_string(),
+            session.token.refresh_token.into_string(),
+            session.token.expires_at,
+        );
+
+        Ok(builder)
+    }
+
     /// Create a client builder that can be configure
     ///
     /// The helloasso client can be created ether by calling the `new` method
     /// or by using the builder pattern for a higher flexibility.
     ///
-    /// ```rust
-    /// # use helloasso::{HelloAsso, Error};
+    /// ```rust,no_run
+    /// # use helloasso::{Environment, HelloAsso, Error};
     /// # use dotenv::dotenv;
     /// # use std::env;
     /// #
@@ -78,7 +283,7 @@ impl HelloAsso {
     /// # let client_secret = env::var("CLIENT_SECRET").unwrap();
     /// #
     /// let client = HelloAsso::builder(client_id, client_secret)
-    /// #   .set_url("https://api.helloasso-sandbox.com/v5", "https://api.helloasso-sandbox.com/oauth2/token")?
+    /// #   .environment(Environment::Sandbox)
     ///     .get_token()
     ///     .await?
     ///     .config_client()?
@@ -89,272 +294,2447 @@ impl HelloAsso {
     pub fn builder(client_id: String, client_secret: String) -> HelloAssoBuilder {
         HelloAssoBuilder {
             client_id,
-            client_secret,
-            url: Url::from_str(URL).expect("Config url is always valid"),
-            token_url: Url::from_str(OAUTH2_TOKEN_URL).expect("Config url is always valid"),
+            client_secret: client_secret.into(),
+            url: Url::from_str(DEFAULT_ENVIRONMENT.api_url()).expect("Config url is always valid"),
+            token_url: Url::from_str(DEFAULT_ENVIRONMENT.token_url())
+                .expect("Config url is always valid"),
+            authorize_url: default_authorize_url(),
             access_token: None,
             refresh_token: None,
             token_type: None,
             token_outdated_after: None,
+            refresh_margin: REFRESH_MARGIN,
+            refresh_jitter: Duration::ZERO,
             client: None,
+            token_client: None,
+            http_client: None,
+            transport: None,
+            compression: true,
+            reauthenticate_on_unauthorized: true,
+            decode_mode: DecodeMode::Lenient,
+            timeout: None,
+            connect_timeout: None,
+            proxy: None,
+            no_proxy: false,
+            retry_policy: RetryPolicy::NONE,
+            rate_limit: None,
+            on_token_rotated: None,
+            on_retry: None,
+            interceptors: Interceptors::default(),
         }
     }
 
-    /// Refresh the access_token of the client
+    /// Check that the api can be reached and return the round trip time
     ///
-    /// By default access token are only valid for 30 min,
-    /// we can use this function to reset this timer
-    pub async fn refresh_token(&mut self) -> Result<&mut Self, reqwest::Error> {
-        // Prepare request body
-        let mut tokens = HashMap::new();
-        tokens.insert("client_id", self.client_id.clone());
-        tokens.insert("refresh_token", self.refresh_token.clone());
-        tokens.insert("grant_type", "refresh_token".to_string());
+    /// No credentials are needed, any http answer from the api is a success,
+    /// only a network failure is an error. `base_url` default to the api url.
+    pub async fn ping(base_url: Option<&str>) -> Result<Duration> {
+        let url =
+            Url::from_str(base_url.unwrap_or(DEFAULT_ENVIRONMENT.api_url())).map_err(|err| {
+                log_error!("Can't parse url {:?}", base_url);
+                Error::ParseUrlErr(err)
+            })?;
 
-        // Get access and refresh token
-        let answer_client = reqwest::Client::new();
-        let token = answer_client
-            .post(self.token_url.as_ref())
-            .form(&tokens)
+        let start = Instant::now();
+        reqwest::Client::new()
+            .head(url)
             .send()
             .await
             .map_err(|err| {
-                #[cfg(feature = "log")]
-                error!("Can't fetch refresh token from the api");
-                err
-            })?
-            .json::<RefreshToken>()
-            .await
-            .map_err(|err| {
-                #[cfg(feature = "log")]
-                error!("Can't deserialize refresh token response");
-                err
+                log_error!("Can't reach the api");
+                Error::ReqwestErr(err)
             })?;
+        let elapsed = start.elapsed();
 
-        // Fill data
-        self.access_token = token.access_token;
-        self.refresh_token = token.refresh_token;
-        self.token_outdated_after = SystemTime::now() + Duration::from_secs(token.expires_in);
+        log_info!("Api reached in {:?}", elapsed);
+        Ok(elapsed)
+    }
 
-        #[cfg(feature = "log")]
-        info!("Access token refreshed");
+    /// Refresh the access_token of the client
+    ///
+    /// By default access token are only valid for 30 min,
+    /// we can use this function to reset this timer.
+    /// The token is also refreshed before sending a request when it is about to expire.
+    /// The new tokens are used by every clone of the client.
+    pub async fn refresh_token(&self) -> Result<&Self> {
+        let _refreshing = self.refresh_lock.lock().await;
+        self.refresh().await?;
         Ok(self)
     }
-}
 
-#[derive(Debug, Deserialize)]
-pub struct HelloAssoBuilder {
-    pub client_id: String,
-    client_secret: String,
-    pub url: Url,
-    token_url: Url,
-    access_token: Option<String>,
-    refresh_token: Option<String>,
-    token_type: Option<String>,
-    token_outdated_after: Option<SystemTime>,
-    #[serde(skip)]
-    client: Option<reqwest::Client>,
-}
+    /// Refresh the access token in a background task, a bit before it expires
+    ///
+    /// The token stays valid even when the client is idle for a long time. The
+    /// task runs until it is aborted with the returned handle or every clone of the
+    /// client is dropped, a failed refresh is retried after 30 seconds. A client
+    /// without refresh token stops the task.
+    #[cfg(feature = "tokio")]
+    pub fn spawn_auto_refresh(&self) -> tokio::task::JoinHandle<()> {
+        let client = self.clone();
 
-#[derive(Debug, Deserialize)]
-struct AccessTokenResponse {
-    access_token: String,
-    refresh_token: String,
-    token_type: String,
-    expires_in: u64,
-}
+        tokio::spawn(async move {
+            loop {
+                // The task holds the last clone of the client, no one can use the tokens anymore
+                if Arc::strong_count(&client.tokens) == 1 {
+                    log_info!("Client dropped, the background refresh is stopped");
+                    return;
+                }
+                if client
+                    .token_state()
+                    .refresh_token
+                    .expose_secret()
+                    .is_empty()
+                {
+                    log_warn!("No refresh token, the background refresh is stopped");
+                    return;
+                }
+                if let Err(err) = client.refresh_if_needed().await {
+                    log_warn!("Background refresh of the token failed, {}", err);
+                }
 
-impl HelloAssoBuilder {
-    /// Set the client url. You need to call this methode before get_token and config_client
-    pub fn set_url(&mut self, url: &str, token_url: &str) -> Result<&mut Self, Error> {
-        self.url = Url::from_str(url).map_err(|err| {
-            #[cfg(feature = "log")]
-            error!("Can't parse url {}", url);
-            Error::ParseUrlErr(err)
-        })?;
-        self.token_url = Url::from_str(token_url).map_err(|err| {
-            #[cfg(feature = "log")]
-            error!("Can't parse token_url {}", token_url);
-            Error::ParseUrlErr(err)
-        })?;
+                // The floor keeps a token living less than the refresh margin from being
+                // refreshed in a loop, and spaces the retries of a failed refresh
+                let delay = client.time_before_refresh().max(AUTO_REFRESH_RETRY);
+                sleep(delay.min(AUTO_REFRESH_CHECK)).await;
+            }
+        })
+    }
 
-        #[cfg(feature = "log")]
-        info!("Client urls set to {} {}", self.url, self.token_url);
-        Ok(self)
+    /// How long until the token should be refreshed, zero when it is already outdated
+    #[cfg(feature = "tokio")]
+    fn time_before_refresh(&self) -> Duration {
+        let tokens = self.token_state();
+        (tokens.expires_at - tokens.refresh_before - Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO)
     }
 
-    /// Get the access token using the client id an secret
-    pub async fn get_token(&mut self) -> Result<&mut Self, Error> {
+    /// Refresh the tokens, shared with every clone of the client
+    async fn refresh(&self) -> Result<()> {
         // Prepare request body
         let mut tokens = HashMap::new();
         tokens.insert("client_id", self.client_id.clone());
-        tokens.insert("client_secret", self.client_secret.clone());
-        tokens.insert("grant_type", "client_credentials".to_string());
+        let refresh_token = self.token_state().refresh_token;
+        tokens.insert("refresh_token", refresh_token.expose_secret().to_string());
+        tokens.insert("grant_type", "refresh_token".to_string());
 
         // Get access and refresh token
-        let answer_client = reqwest::Client::new();
-        let response = answer_client
+        let request = self
+            .client
             .post(self.token_url.as_ref())
             .form(&tokens)
-            .send()
+            .build()?;
+        // A revoked or rotated refresh token is refused with an authentication error
+        let token: RefreshToken = fetch_access_token(&*self.token_transport, request)
             .await
-            .map_err(|err| {
-                #[cfg(feature = "log")]
-                error!("Can't fetch access token");
-                Error::ReqwestErr(err)
-            })?;
+            .inspect_err(|_| log_error!("Can't refresh the access token"))?;
 
-        match response.status() {
-            StatusCode::OK => {
-                let token = response
-                    .json::<AccessTokenResponse>()
-                    .await
-                    .map_err(|err| {
-                        #[cfg(feature = "log")]
-                        error!("Can't decode access token");
-                        Error::DecodeErr(err)
-                    })?;
-
-                // Fill data
-                self.access_token = Some(token.access_token);
-                self.refresh_token = Some(token.refresh_token);
-                self.token_type = Some(token.token_type);
-                self.token_outdated_after =
-                    Some(SystemTime::now() + Duration::from_secs(token.expires_in));
-
-                #[cfg(feature = "log")]
-                info!("Access token fetched");
-
-                Ok(self)
-            }
-            StatusCode::BAD_REQUEST => {
-                let error = response
-                    .json::<AuthenticationError>()
-                    .await
-                    .map_err(|err| {
-                        #[cfg(feature = "log")]
-                        error!("Can't decode authentication error");
-                        Error::DecodeErr(err)
-                    })?;
+        self.set_tokens(token.access_token, token.refresh_token, token.expires_in);
 
-                #[cfg(feature = "log")]
-                error!("An authentication error as occur, wrong client_id or credential");
+        log_info!("Access token refreshed");
+        Ok(())
+    }
 
-                Err(Error::AuthErr(error))
-            }
-            status => {
-                unimplemented!(
-                    "Unknown status code while fetching the access_token, {}",
-                    status
-                )
-            }
+    /// Fetch new tokens with the client credentials, after the api refused `refused_token`
+    ///
+    /// Nothing is done when another request already replaced the refused token.
+    async fn reauthenticate(&self, refused_token: &Secret) -> Result<()> {
+        let _refreshing = self.refresh_lock.lock().await;
+        if self.token_state().access_token != *refused_token {
+            return Ok(());
         }
-    }
 
-    /// Create a new client using a previously set access_token, see `get_token`
-    pub fn config_client(&mut self) -> Result<&mut Self, Error> {
-        let mut headers = header::HeaderMap::new();
-        headers.insert(
-            header::AUTHORIZATION,
-            format!(
-                "Bearer {}",
-                self.access_token
-                    .clone()
-                    .expect("Can't get the access_token, use get_token")
-            )
-            .parse()
-            .expect("Can't parse formatted token into a HeaderName"),
-        );
-        self.client = Some(
-            reqwest::Client::builder()
-                .default_headers(headers)
-                .build()
-                .map_err(Error::ReqwestErr)?,
+        let mut tokens = HashMap::new();
+        tokens.insert("client_id", self.client_id.clone());
+        tokens.insert(
+            "client_secret",
+            self.client_secret.expose_secret().to_string(),
         );
+        tokens.insert("grant_type", "client_credentials".to_string());
 
-        #[cfg(feature = "log")]
-        info!("Client configured");
-        Ok(self)
+        let request = self
+            .client
+            .post(self.token_url.as_ref())
+            .form(&tokens)
+            .build()
+            .map_err(Error::ReqwestErr)?;
+        let token: AccessTokenResponse =
+            fetch_access_token(&*self.token_transport, request).await?;
+        self.set_tokens(token.access_token, token.refresh_token, token.expires_in);
+
+        log_info!("Authenticated again after the access token was refused");
+        Ok(())
     }
 
-    /// Build the client
-    pub fn build(&mut self) -> HelloAsso {
+    /// Post a form to the token endpoint, the client id and secret are added to it
+    pub(crate) async fn request_token<T: DeserializeOwned>(
+        &self,
+        form: &[(&str, &str)],
+    ) -> Result<T> {
+        let mut tokens = vec![
+            ("client_id", self.client_id.as_str()),
+            ("client_secret", self.client_secret.expose_secret()),
+        ];
+        tokens.extend_from_slice(form);
+
+        let request = self
+            .client
+            .post(self.token_url.as_ref())
+            .form(&tokens)
+            .build()
+            .map_err(Error::ReqwestErr)?;
+        fetch_access_token(&*self.token_transport, request).await
+    }
+
+    /// The page where an organization authorizes the client
+    pub(crate) fn authorize_endpoint(&self) -> Url {
+        self.authorize_url.clone()
+    }
+
+    /// A client sending its requests with `token`, not shared with this client
+    ///
+    /// The client never authenticates again with the client credentials, it would
+    /// replace the token with a token of the client.
+    pub(crate) fn with_token(
+        &self,
+        token: StoredToken,
+        on_token_rotated: Option<TokenRotatedHook>,
+    ) -> HelloAsso {
         HelloAsso {
-            client_id: self.client_id.clone(),
-            client_secret: self.client_secret.clone(),
-            url: self.url.clone(),
-            token_url: self.token_url.clone(),
-            access_token: self.access_token.clone().unwrap_or_default(),
-            refresh_token: self.refresh_token.clone().unwrap_or_default(),
-            token_outdated_after: self.token_outdated_after.unwrap_or(SystemTime::UNIX_EPOCH),
-            client: self.client.clone().unwrap_or_default(),
+            tokens: Arc::new(RwLock::new(TokenState::new(
+                token,
+                self.refresh_margin + jitter(self.refresh_jitter),
+            ))),
+            refresh_lock: Arc::new(Mutex::new(())),
+            reauthenticate_on_unauthorized: false,
+            on_token_rotated,
+            ..self.clone()
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use crate::{Error, HelloAsso};
-    use dotenv::dotenv;
-    #[cfg(feature = "log")]
-    use log::{info, warn};
-    use std::env;
+    /// A clone sharing the tokens of this client, sending its requests with `transport`
+    #[cfg(feature = "blocking")]
+    pub(crate) fn with_transport(&self, transport: SharedTransport) -> HelloAsso {
+        HelloAsso {
+            transport: transport.clone(),
+            token_transport: transport,
+            ..self.clone()
+        }
+    }
 
-    pub fn get_env_variables() -> (String, String) {
-        if let Err(err) = dotenv() {
-            #[cfg(feature = "log")]
-            warn!("Can't load .env file, {}", err);
-        } else {
-            #[cfg(feature = "log")]
-            info!(".env file loaded");
+    /// Replace the tokens shared with every clone of the client
+    fn set_tokens(&self, access_token: String, refresh_token: String, expires_in: u64) {
+        let stored_token = StoredToken {
+            access_token: access_token.into(),
+            refresh_token: refresh_token.into(),
+            expires_at: Utc::now() + Duration::from_secs(expires_in),
+        };
+        *self.tokens.write().expect("Token lock is never poisoned") = TokenState::new(
+            stored_token.clone(),
+            self.refresh_margin + jitter(self.refresh_jitter),
+        );
+
+        if let Some(hook) = &self.on_token_rotated {
+            hook.call(&stored_token);
         }
+    }
 
-        let client_id = env::var("CLIENT_ID").unwrap();
-        let client_secret = env::var("CLIENT_SECRET").unwrap();
+    /// Refresh the token when it is about to expire
+    ///
+    /// A client without refresh token, ie built without calling `get_token`, is left as is.
+    async fn refresh_if_needed(&self) -> Result<()> {
+        if !self.needs_refresh() {
+            return Ok(());
+        }
 
-        (client_id, client_secret)
+        let _refreshing = self.refresh_lock.lock().await;
+        // Another request may have refreshed the token while waiting for the lock
+        if !self.needs_refresh() || self.token_state().refresh_token.expose_secret().is_empty() {
+            return Ok(());
+        }
+
+        self.refresh().await
     }
 
-    #[tokio::test]
-    async fn new_client() {
-        let (client_id, client_secret) = get_env_variables();
+    /// The current tokens of the client
+    pub fn stored_token(&self) -> StoredToken {
+        self.token_state().stored()
+    }
 
-        HelloAsso::new(client_id, client_secret)
-            .await
-            .expect("Test failed");
+    fn token_state(&self) -> TokenState {
+        self.tokens
+            .read()
+            .expect("Token lock is never poisoned")
+            .clone()
     }
 
-    #[tokio::test]
-    async fn invalid_client_id() {
-        let (_, client_secret) = get_env_variables();
-        let client_id = "abc".to_string();
+    /// The session of the client, to restore it later with `from_session`
+    pub fn session(&self) -> Session {
+        Session {
+            token: self.stored_token(),
+            api_url: Some(self.url.clone()),
+            token_url: Some(self.token_url.clone()),
+            authorize_url: Some(self.authorize_url.clone()),
+        }
+    }
+
+    /// Persist the current tokens with the `on_token_rotated` callback
+    ///
+    /// Meant to be called on shutdown, a refresh in progress is waited for so the
+    /// persisted tokens are the rotated ones.
+    pub async fn flush(&self) -> Result<()> {
+        let _refreshing = self.refresh_lock.lock().await;
+        if let Some(hook) = &self.on_token_rotated {
+            hook.call(&self.stored_token());
+            log_info!("Tokens flushed");
+        }
 
-        let client = HelloAsso::new(client_id, client_secret).await;
+        Ok(())
+    }
 
-        assert!(matches!(client, Err(Error::AuthErr(_))))
+    /// Whether the access token should be refreshed
+    ///
+    /// The token is considered outdated a bit before its expiration, the refresh margin
+    /// plus a random jitter, see `refresh_jitter` on the builder. The jitter can only
+    /// make the refresh happen sooner, never after the token expiration.
+    pub fn needs_refresh(&self) -> bool {
+        let tokens = self.tokens.read().expect("Token lock is never poisoned");
+        Utc::now() + tokens.refresh_before >= tokens.expires_at
     }
 
-    #[tokio::test]
-    async fn invalid_client_secret() {
-        let (client_id, _) = get_env_variables();
-        let client_secret = "abc".to_string();
+    /// When the access token expires
+    pub fn token_expires_at(&self) -> DateTime<Utc> {
+        self.token_state().expires_at
+    }
+
+    /// How long the access token is still valid, zero when it is expired
+    pub fn token_expires_in(&self) -> Duration {
+        (self.token_expires_at() - Utc::now())
+            .to_std()
+            .unwrap_or(Duration::ZERO)
+    }
 
-        let client = HelloAsso::new(client_id, client_secret).await;
+    /// Whether the access token is still valid in `margin`, ie to call `refresh_token` when it isn't
+    ///
+    /// Unlike `needs_refresh`, the refresh margin and jitter of the client aren't taken into account.
+    pub fn is_token_valid(&self, margin: Duration) -> bool {
+        self.token_expires_in() > margin
+    }
 
-        assert!(matches!(client, Err(Error::AuthErr(_))))
+    /// Build the url of an api endpoint from its path segments
+    ///
+    /// Each segment is percent encoded, a slug can't escape its place in the path.
+    pub(crate) fn endpoint(&self, segments: &[&str]) -> Url {
+        let mut url = self.url.clone();
+        url.path_segments_mut()
+            .expect("Config url is always a base")
+            .pop_if_empty()
+            .extend(segments);
+        url
     }
 
-    #[tokio::test]
-    async fn refresh_token() {
-        let (client_id, client_secret) = get_env_variables();
+    /// Send a get request to any endpoint of the api
+    ///
+    /// Can be used to reach endpoints that are not wrapped by this crate yet.
+    /// The `path` is relative to the api url, ie `organizations/{slug}/forms`,
+    /// the `query` values are percent encoded.
+    pub async fn get_raw(&self, path: &str, query: &[(&str, &str)]) -> Result<Value> {
+        self.get(self.raw_endpoint(path), query).await
+    }
+
+    /// Send a post request with a json body to any endpoint of the api, see `get_raw`
+    pub async fn post_raw(
+        &self,
+        path: &str,
+        query: &[(&str, &str)],
+        body: &Value,
+    ) -> Result<Value> {
+        self.post(self.raw_endpoint(path), query, body).await
+    }
+
+    fn raw_endpoint(&self, path: &str) -> Url {
+        let segments = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect::<Vec<_>>();
+        self.endpoint(&segments)
+    }
+
+    /// Send a get request to the api and decode the response
+    pub(crate) async fn get<T: DeserializeOwned, Q: Serialize + ?Sized>(
+        &self,
+        url: Url,
+        query: &Q,
+    ) -> Result<T> {
+        self.send(self.client.get(url).query(query)).await
+    }
+
+    /// Same as `get`, along with the metadata of the response
+    pub(crate) async fn get_with_meta<T: DeserializeOwned, Q: Serialize + ?Sized>(
+        &self,
+        url: Url,
+        query: &Q,
+    ) -> Result<(T, ResponseMeta)> {
+        self.send_with_meta(self.client.get(url).query(query)).await
+    }
+
+    /// Get every page of a list endpoint
+    pub(crate) async fn get_all_pages<T: DeserializeOwned>(
+        &self,
+        url: Url,
+        query: &[(&str, String)],
+    ) -> Result<Vec<T>> {
+        let mut elements = Vec::new();
+        let mut params = PaginationParams::page(1, PAGE_SIZE);
+
+        loop {
+            let mut page_query = query.to_vec();
+            page_query.extend(params.query());
+
+            let page = self
+                .get::<Paginated<T>, _>(url.clone(), &page_query)
+                .await?;
+            let next_page = page.next_page();
+            elements.extend(page.data);
+
+            match next_page {
+                Some(next_page) => params = next_page,
+                None => return Ok(elements),
+            }
+        }
+    }
 
-        let mut client = HelloAsso::new(client_id, client_secret)
+    /// Send a post request with a json body to the api and decode the response
+    pub(crate) async fn post<T: DeserializeOwned, Q: Serialize + ?Sized, B: Serialize + ?Sized>(
+        &self,
+        url: Url,
+        query: &Q,
+        body: &B,
+    ) -> Result<T> {
+        self.send(self.client.post(url).query(query).json(body))
             .await
-            .expect("Can't create the client");
+    }
 
-        client
-            .refresh_token()
+    /// Send a put request with a json body to the api and decode the response
+    pub(crate) async fn put<T: DeserializeOwned, Q: Serialize + ?Sized, B: Serialize + ?Sized>(
+        &self,
+        url: Url,
+        query: &Q,
+        body: &B,
+    ) -> Result<T> {
+        self.send(self.client.put(url).query(query).json(body))
             .await
-            .expect("Could not refresh token");
+    }
+
+    /// Send a delete request to the api and decode the response
+    pub(crate) async fn delete<T: DeserializeOwned, Q: Serialize + ?Sized>(
+        &self,
+        url: Url,
+        query: &Q,
+    ) -> Result<T> {
+        self.send(self.client.delete(url).query(query)).await
+    }
+
+    /// Send a request to the api and decode the response
+    ///
+    /// The access token is refreshed first when it is about to expire.
+    /// Transient failures are retried according to the retry policy.
+    async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T> {
+        let (value, _) = self.send_with_meta(request).await?;
+        Ok(value)
+    }
+
+    /// Send a request to the api, decode the response and keep its metadata
+    async fn send_with_meta<T: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+    ) -> Result<(T, ResponseMeta)> {
+        self.refresh_if_needed().await?;
+        let request = request.build().map_err(Error::ReqwestErr)?;
+
+        let access_token = self.token_state().access_token;
+        match self.send_with_token(&request, &access_token).await {
+            Err(Error::AuthErr(_)) if self.can_reauthenticate() => {
+                log_warn!("The access token was refused, authenticating again");
+
+                self.reauthenticate(&access_token).await?;
+                self.send_with_token(&request, &self.token_state().access_token)
+                    .await
+            }
+            result => result,
+        }
+    }
+
+    /// Whether a request refused with a `401 Unauthorized` can be sent again after authenticating
+    ///
+    /// A client restored from a session has no client secret to authenticate with.
+    fn can_reauthenticate(&self) -> bool {
+        self.reauthenticate_on_unauthorized && !self.client_secret.expose_secret().is_empty()
+    }
+
+    /// Send a request authenticated with `access_token` and decode the response
+    async fn send_with_token<T: DeserializeOwned>(
+        &self,
+        request: &reqwest::Request,
+        access_token: &Secret,
+    ) -> Result<(T, ResponseMeta)> {
+        let mut request = request
+            .try_clone()
+            .expect("Api requests never have a streaming body");
+        let mut authorization =
+            HeaderValue::from_str(&format!("Bearer {}", access_token.expose_secret()))
+                .map_err(|_| Error::InvalidInput("invalid access token".to_string()))?;
+        authorization.set_sensitive(true);
+        request
+            .headers_mut()
+            .insert(header::AUTHORIZATION, authorization);
+
+        #[cfg(feature = "tracing")]
+        let span = crate::logging::request_span(&request);
+        let response = self.execute(request);
+        #[cfg(feature = "tracing")]
+        let response = tracing::Instrument::instrument(response, span);
+
+        response.await
+    }
+
+    /// Execute a request, retrying transient failures, and decode the response
+    async fn execute<T: DeserializeOwned>(
+        &self,
+        request: reqwest::Request,
+    ) -> Result<(T, ResponseMeta)> {
+        let start = Instant::now();
+        let mut attempt = 1;
+        loop {
+            let mut current = request
+                .try_clone()
+                .expect("Api requests never have a streaming body");
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire().await;
+            }
+            self.interceptors.on_request(&mut current);
+            let result = self.transport.execute(current).await;
+            if let Ok(response) = &result {
+                self.interceptors.on_response(response);
+            }
+
+            let cause = match &result {
+                Err(Error::ReqwestErr(err)) if err.is_connect() || err.is_timeout() => {
+                    Some(err.to_string())
+                }
+                Ok(response) if is_transient(response.status()) => {
+                    Some(response.status().to_string())
+                }
+                _ => None,
+            };
+            // The delay asked by the api wins over the backoff, unless it is too long to wait
+            let retry_after = result.as_ref().ok().and_then(retry_after);
+            let delay = retry_after.unwrap_or_else(|| self.retry_policy.delay(attempt));
+            let retry = attempt < self.retry_policy.max_attempts
+                && retry_after.is_none_or(|delay| delay <= self.retry_policy.max_delay);
+
+            if let Some(cause) = cause.filter(|_| retry) {
+                let event = RetryEvent {
+                    attempt,
+                    max_attempts: self.retry_policy.max_attempts,
+                    delay,
+                    cause,
+                    retry_after,
+                };
+                log_warn!(
+                    "Request attempt {}/{} failed with {}, retrying in {:?} (retry after {:?})",
+                    event.attempt,
+                    event.max_attempts,
+                    event.cause,
+                    event.delay,
+                    event.retry_after
+                );
+                if let Some(hook) = &self.on_retry {
+                    hook.call(&event);
+                }
+
+                sleep(delay).await;
+                attempt += 1;
+                continue;
+            }
+
+            let response = result.inspect_err(|_| log_error!("Can't send request to the api"))?;
+            record_response(response.status(), start.elapsed());
+
+            let meta = ResponseMeta::new(response.status(), response.headers());
+            return handle_response(response, self.decode_mode)
+                .await
+                .map(|value| (value, meta));
+        }
+    }
+}
+
+/// The delay before retrying asked by a `429 Too Many Requests` response
+///
+/// `Retry-After` is either a number of seconds or an http date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    if response.status() != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    let retry_after = response
+        .headers()
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+
+    match retry_after.parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => {
+            let date = DateTime::parse_from_rfc2822(retry_after).ok()?;
+            Some(
+                (date.with_timezone(&Utc) - Utc::now())
+                    .to_std()
+                    .unwrap_or_default(),
+            )
+        }
+    }
+}
+
+/// Whether a response status is worth retrying
+fn is_transient(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Compare the tokens of two clients
+fn same_tokens(a: &Arc<RwLock<TokenState>>, b: &Arc<RwLock<TokenState>>) -> bool {
+    if Arc::ptr_eq(a, b) {
+        return true;
+    }
+
+    let a = a.read().expect("Token lock is never poisoned");
+    let b = b.read().expect("Token lock is never poisoned");
+    a.same_tokens(&b)
+}
+
+/// Decode a response of the api, every endpoint goes through this function
+///
+/// A `204 No Content` or an empty body is decoded as `null`,
+/// so the response of an endpoint returning nothing can be decoded as `()`.
+/// The error statuses are turned into the matching [Error](crate::Error).
+pub(crate) async fn handle_response<T: DeserializeOwned>(
+    response: Response,
+    mode: DecodeMode,
+) -> Result<T> {
+    if response.status().is_success() {
+        return decode_body(response, mode)
+            .await
+            .inspect_err(|_| log_error!("Can't decode api response"));
+    }
+
+    // Kept in the error, the request id helps the helloasso support
+    let meta = Some(Box::new(ResponseMeta::new(
+        response.status(),
+        response.headers(),
+    )));
+    match response.status() {
+        status @ StatusCode::BAD_REQUEST => {
+            let body = response.text().await.unwrap_or_default();
+            let error = ApiError::from_body(&body).unwrap_or_else(|| ApiError {
+                message: Some(body.clone()).filter(|body| !body.is_empty()),
+                ..ApiError::default()
+            });
+
+            log_error!("The request was refused by the api, {}", error);
+
+            Err(Error::BadRequest {
+                status,
+                error,
+                body,
+                meta,
+            })
+        }
+        StatusCode::UNAUTHORIZED => {
+            let body = response.text().await.unwrap_or_default();
+            let mut error =
+                serde_json::from_str::<AuthenticationError>(&body).unwrap_or_else(|_| {
+                    AuthenticationError {
+                        error: "unauthorized".to_string(),
+                        error_description: error_message(&body),
+                        meta: None,
+                    }
+                });
+            error.meta = meta;
+
+            log_error!("The access token was refused by the api");
+
+            Err(Error::AuthErr(error))
+        }
+        StatusCode::FORBIDDEN => {
+            let body = response.text().await.unwrap_or_default();
+            let mut error =
+                serde_json::from_str::<AuthorizationError>(&body).unwrap_or_else(|_| {
+                    AuthorizationError {
+                        message: error_message(&body),
+                        meta: None,
+                    }
+                });
+            error.meta = meta;
+
+            log_error!("Your JWT token hasn't the privileges or Roles for this action");
+
+            Err(Error::PermErr(error))
+        }
+        StatusCode::TOO_MANY_REQUESTS => {
+            let retry_after = retry_after(&response);
+
+            log_error!("Rate limited by the api, retry after {:?}", retry_after);
+
+            Err(Error::RateLimited { retry_after, meta })
+        }
+        StatusCode::NOT_FOUND => {
+            log_error!("Resource not found {}", response.url());
+
+            Err(Error::NotFound {
+                resource: "resource",
+                id: response.url().path().to_string(),
+                meta,
+            })
+        }
+        status => {
+            log_error!(
+                "Unexpected status code while requesting the api, {}",
+                status
+            );
+
+            Err(unexpected_status(response).await)
+        }
+    }
+}
+
+/// The message of an error response, the body is not always an error of the api, ie it can be empty
+fn error_message(body: &str) -> String {
+    ApiError::from_body(body).map_or_else(|| body.to_string(), |error| error.to_string())
+}
+
+/// An [UnexpectedStatus](crate::Error::UnexpectedStatus) error with the body of the response
+pub(crate) async fn unexpected_status(response: Response) -> Error {
+    let status = response.status();
+    let meta = Some(Box::new(ResponseMeta::new(status, response.headers())));
+    let body = response.text().await.unwrap_or_default();
+    let error = ApiError::from_body(&body);
+
+    Error::UnexpectedStatus {
+        status,
+        error,
+        body,
+        meta,
+    }
+}
+
+/// Read and decode the json body of a response, an empty body is decoded as `null`
+async fn decode_body<T: DeserializeOwned>(response: Response, mode: DecodeMode) -> Result<T> {
+    let body = response.bytes().await.map_err(|err| {
+        log_error!("Can't read response body");
+        Error::ReqwestErr(err)
+    })?;
+    let body = if body.is_empty() { b"null" } else { &body[..] };
+
+    decode_json(body, mode)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HelloAssoBuilder {
+    pub client_id: String,
+    client_secret: Secret,
+    pub url: Url,
+    token_url: Url,
+    #[serde(default = "default_authorize_url")]
+    authorize_url: Url,
+    access_token: Option<Secret>,
+    refresh_token: Option<Secret>,
+    token_type: Option<String>,
+    token_outdated_after: Option<DateTime<Utc>>,
+    #[serde(default = "default_refresh_margin")]
+    refresh_margin: Duration,
+    #[serde(default)]
+    refresh_jitter: Duration,
+    #[serde(skip)]
+    client: Option<reqwest::Client>,
+    #[serde(skip)]
+    token_client: Option<reqwest::Client>,
+    /// The http client supplied by the user, used instead of building one
+    #[serde(skip)]
+    http_client: Option<reqwest::Client>,
+    #[serde(skip)]
+    transport: Option<SharedTransport>,
+    #[serde(default = "enabled")]
+    compression: bool,
+    #[serde(default = "enabled")]
+    reauthenticate_on_unauthorized: bool,
+    #[serde(skip)]
+    decode_mode: DecodeMode,
+    #[serde(default)]
+    timeout: Option<Duration>,
+    #[serde(default)]
+    connect_timeout: Option<Duration>,
+    #[serde(skip)]
+    proxy: Option<reqwest::Proxy>,
+    #[serde(default)]
+    no_proxy: bool,
+    #[serde(skip)]
+    retry_policy: RetryPolicy,
+    #[serde(skip)]
+    rate_limit: Option<RateLimit>,
+    #[serde(skip)]
+    on_token_rotated: Option<TokenRotatedHook>,
+    #[serde(skip)]
+    on_retry: Option<RetryHook>,
+    #[serde(skip)]
+    interceptors: Interceptors,
+}
+
+/// Fetch an access token, with the client credentials or an authorization code
+async fn fetch_access_token<T: DeserializeOwned>(
+    transport: &dyn HttpTransport,
+    request: reqwest::Request,
+) -> Result<T> {
+    let response = transport
+        .execute(request)
+        .await
+        .inspect_err(|_| log_error!("Can't fetch access token"))?;
+
+    match response.status() {
+        StatusCode::BAD_REQUEST => {
+            let meta = ResponseMeta::new(response.status(), response.headers());
+            let mut error = decode_body::<AuthenticationError>(response, DecodeMode::Lenient)
+                .await
+                .inspect_err(|_| log_error!("Can't decode authentication error"))?;
+            error.meta = Some(Box::new(meta));
+
+            log_error!("An authentication error as occur, wrong client_id or credential");
+
+            Err(Error::AuthErr(error))
+        }
+        _ => handle_response(response, DecodeMode::Lenient)
+            .await
+            .inspect_err(|_| log_error!("Can't fetch access token")),
+    }
+}
+
+/// Read an environment variable
+fn env_var(var: &'static str) -> Result<String> {
+    env::var(var).map_err(|_| {
+        log_error!("Environment variable {} is missing", var);
+        Error::MissingEnv { var }
+    })
+}
+
+fn default_authorize_url() -> Url {
+    Url::from_str(DEFAULT_ENVIRONMENT.authorize_url()).expect("Config url is always valid")
+}
+
+fn enabled() -> bool {
+    true
+}
+
+fn default_refresh_margin() -> Duration {
+    REFRESH_MARGIN
+}
+
+#[derive(Debug, Deserialize)]
+struct AccessTokenResponse {
+    access_token: String,
+    refresh_token: String,
+    token_type: String,
+    expires_in: u64,
+}
+
+impl HelloAssoBuilder {
+    /// Set the client url. You need to call this methode before get_token and config_client
+    pub fn set_url(&mut self, url: &str, token_url: &str) -> Result<&mut Self> {
+        self.url = Url::from_str(url).map_err(|err| {
+            log_error!("Can't parse url {}", url);
+            Error::ParseUrlErr(err)
+        })?;
+        self.token_url = Url::from_str(token_url).map_err(|err| {
+            log_error!("Can't parse token_url {}", token_url);
+            Error::ParseUrlErr(err)
+        })?;
+
+        log_info!("Client urls set to {} {}", self.url, self.token_url);
+        Ok(self)
+    }
+
+    /// Use another host, ie a local mock server, the api and token urls are derived from it.
+    /// You need to call this methode before get_token and config_client
+    ///
+    /// `http://localhost:8080` uses `http://localhost:8080/v5` and `http://localhost:8080/oauth2/token`.
+    pub fn base_url(&mut self, base_url: &str) -> Result<&mut Self> {
+        let base_url = base_url.trim_end_matches('/');
+        self.authorize_url = Url::from_str(&format!("{base_url}/authorize"))?;
+        self.set_url(
+            &format!("{base_url}/v5"),
+            &format!("{base_url}/oauth2/token"),
+        )
+    }
+
+    /// Use the api and token urls of an environment.
+    /// You need to call this methode before get_token and config_client
+    pub fn environment(&mut self, environment: Environment) -> &mut Self {
+        self.authorize_url =
+            Url::from_str(environment.authorize_url()).expect("Environment urls are always valid");
+        self.set_url(environment.api_url(), environment.token_url())
+            .expect("Environment urls are always valid")
+    }
+
+    /// Get the access token using the client id an secret
+    pub async fn get_token(&mut self) -> Result<&mut Self> {
+        // Prepare request body
+        let mut tokens = HashMap::new();
+        tokens.insert("client_id", self.client_id.clone());
+        tokens.insert(
+            "client_secret",
+            self.client_secret.expose_secret().to_string(),
+        );
+        tokens.insert("grant_type", "client_credentials".to_string());
+
+        // Get access and refresh token
+        let request = self
+            .token_client()?
+            .post(self.token_url.as_ref())
+            .form(&tokens)
+            .build()
+            .map_err(Error::ReqwestErr)?;
+        let token: AccessTokenResponse =
+            fetch_access_token(&*self.token_transport()?, request).await?;
+
+        // Fill data
+        self.access_token = Some(token.access_token.into());
+        self.refresh_token = Some(token.refresh_token.into());
+        self.token_type = Some(token.token_type);
+        self.token_outdated_after = Some(Utc::now() + Duration::from_secs(token.expires_in));
+
+        if let Some(hook) = &self.on_token_rotated {
+            hook.call(&self.stored_token());
+        }
+
+        log_info!("Access token fetched");
+
+        Ok(self)
+    }
+
+    /// Use tokens obtained elsewhere instead of calling `get_token`
+    ///
+    /// The access token is refreshed with the refresh token once it expires.
+    /// `expires_at` can be a [SystemTime](std::time::SystemTime) or a `DateTime`.
+    pub fn tokens(
+        &mut self,
+        access_token: String,
+        refresh_token: String,
+        expires_at: impl Into<DateTime<Utc>>,
+    ) -> &mut Self {
+        self.access_token = Some(access_token.into());
+        self.refresh_token = Some(refresh_token.into());
+        self.token_type = Some("bearer".to_string());
+        self.token_outdated_after = Some(expires_at.into());
+        self
+    }
+
+    /// Create the http client used to request the api
+    ///
+    /// The access token is not part of the client, it is added to each request
+    /// so a refreshed token is used right away.
+    pub fn config_client(&mut self) -> Result<&mut Self> {
+        self.client = Some(match &self.http_client {
+            Some(client) => client.clone(),
+            None => self
+                .http_client_builder()
+                .build()
+                .map_err(Error::ReqwestErr)?,
+        });
+
+        log_info!("Client configured");
+        Ok(self)
+    }
+
+    /// Build the client
+    pub fn build(&mut self) -> HelloAsso {
+        // The tokens may come from `tokens`, the token client is then created here so
+        // the refresh is sent with the http client, timeouts and proxy of the builder
+        let token_transport = self.token_transport().unwrap_or_else(|err| {
+            log_error!("Can't create the token client, {}", err);
+            Arc::new(self.client.clone().unwrap_or_default())
+        });
+
+        HelloAsso {
+            client_id: self.client_id.clone(),
+            client_secret: self.client_secret.clone(),
+            url: self.url.clone(),
+            token_url: self.token_url.clone(),
+            authorize_url: self.authorize_url.clone(),
+            tokens: Arc::new(RwLock::new(TokenState::new(
+                self.stored_token(),
+                self.refresh_margin + jitter(self.refresh_jitter),
+            ))),
+            refresh_lock: Arc::new(Mutex::new(())),
+            refresh_margin: self.refresh_margin,
+            refresh_jitter: self.refresh_jitter,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limit.map(RateLimiter::new),
+            reauthenticate_on_unauthorized: self.reauthenticate_on_unauthorized,
+            decode_mode: self.decode_mode,
+            client: self.client.clone().unwrap_or_default(),
+            transport: self
+                .transport
+                .clone()
+                .unwrap_or_else(|| Arc::new(self.client.clone().unwrap_or_default())),
+            token_transport,
+            on_token_rotated: self.on_token_rotated.clone(),
+            on_retry: self.on_retry.clone(),
+            interceptors: self.interceptors.clone(),
+        }
+    }
+
+    /// Set a callback called with the new tokens each time they are fetched or refreshed
+    ///
+    /// The callback is called before `get_token` or `refresh_token` return, it can be used
+    /// to persist the refresh token as soon as it is rotated.
+    pub fn on_token_rotated(
+        &mut self,
+        hook: impl Fn(&StoredToken) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.on_token_rotated = Some(TokenRotatedHook::new(hook));
+        self
+    }
+
+    /// Add an [Interceptor] called on every request to the api, see [Interceptor]
+    pub fn interceptor(&mut self, interceptor: impl Interceptor + 'static) -> &mut Self {
+        self.interceptors.push(interceptor);
+        self
+    }
+
+    /// Enable or disable the gzip, deflate and brotli compression of the responses
+    ///
+    /// Compression is enabled by default, some proxies mangle compressed responses.
+    /// You need to call this methode before get_token and config_client
+    pub fn compression(&mut self, enabled: bool) -> &mut Self {
+        self.compression = enabled;
+        self
+    }
+
+    /// Authenticate again with the client credentials when the api refuses the access token
+    ///
+    /// Enabled by default, the request is sent again once with the new token. A client
+    /// without client secret, ie restored from a session, never authenticates again.
+    pub fn reauthenticate_on_unauthorized(&mut self, enabled: bool) -> &mut Self {
+        self.reauthenticate_on_unauthorized = enabled;
+        self
+    }
+
+    /// How the responses are decoded, leniently by default
+    ///
+    /// The [strict](crate::DecodeMode::Strict) mode fails on the fields and enum values
+    /// the models don't know, ie to spot the changes of the api in CI.
+    pub fn decode_mode(&mut self, mode: DecodeMode) -> &mut Self {
+        self.decode_mode = mode;
+        self
+    }
+
+    /// Timeout of a whole request, from connecting to reading the body of the response
+    ///
+    /// There is no timeout by default. A request that timed out is retried by the
+    /// [retry policy](crate::RetryPolicy). You need to call this methode before get_token and config_client
+    pub fn timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout of the connection to the api, there is none by default
+    ///
+    /// You need to call this methode before get_token and config_client
+    pub fn connect_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Send the oauth and the api requests through a proxy, ie `http://proxy.example.com:8080`
+    ///
+    /// By default the proxy of the `HTTP_PROXY` and `HTTPS_PROXY` environment variables is used.
+    /// You need to call this methode before get_token and config_client
+    pub fn proxy(&mut self, url: &str) -> Result<&mut Self> {
+        self.proxy = Some(reqwest::Proxy::all(url).map_err(|err| {
+            log_error!("Invalid proxy url {}", url);
+            Error::ReqwestErr(err)
+        })?);
+        self.no_proxy = false;
+        Ok(self)
+    }
+
+    /// Don't use any proxy, not even the one of the environment variables
+    ///
+    /// You need to call this methode before get_token and config_client
+    pub fn no_proxy(&mut self) -> &mut Self {
+        self.proxy = None;
+        self.no_proxy = true;
+        self
+    }
+
+    /// How long before its expiration the access token is refreshed, one minute by default
+    ///
+    /// The token is refreshed before sending a request when it expires within this margin.
+    pub fn refresh_margin(&mut self, margin: Duration) -> &mut Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    /// Spread the refresh of the access token over a window of `jitter`
+    ///
+    /// A random delay up to `jitter` is added to the refresh margin, so a fleet
+    /// of clients started at the same time don't refresh their token all at once.
+    pub fn refresh_jitter(&mut self, jitter: Duration) -> &mut Self {
+        self.refresh_jitter = jitter;
+        self
+    }
+
+    /// Retry the requests failing with a transient error, see [RetryPolicy](crate::RetryPolicy)
+    ///
+    /// Requests are not retried by default. Note that a retried `POST` may be
+    /// processed twice if the api fails after handling it.
+    pub fn retry_policy(&mut self, policy: RetryPolicy) -> &mut Self {
+        self.retry_policy = policy;
+        self
+    }
+
+    /// Set a callback called before each retry, see [RetryEvent](crate::RetryEvent)
+    pub fn on_retry(&mut self, hook: impl Fn(&RetryEvent) + Send + Sync + 'static) -> &mut Self {
+        self.on_retry = Some(RetryHook::new(hook));
+        self
+    }
+
+    /// Limit the requests sent to the api, see [RateLimit](crate::RateLimit)
+    ///
+    /// The requests are not limited by default, the requests fetching the tokens never are.
+    pub fn rate_limit(&mut self, limit: RateLimit) -> &mut Self {
+        self.rate_limit = Some(limit);
+        self
+    }
+
+    /// Send the oauth and the api requests with `transport` instead of reqwest
+    ///
+    /// The options configuring the http client, like `proxy` or `timeout`, are
+    /// ignored. See [HttpTransport](crate::HttpTransport).
+    pub fn transport(&mut self, transport: Arc<dyn HttpTransport>) -> &mut Self {
+        self.transport = Some(transport);
+        self
+    }
+
+    /// Send the oauth and the api requests through a client with middlewares, ie retries or tracing
+    ///
+    /// Same as `transport` with the client, the retry policy of this builder still applies.
+    #[cfg(feature = "reqwest-middleware")]
+    pub fn middleware_client(
+        &mut self,
+        client: reqwest_middleware::ClientWithMiddleware,
+    ) -> &mut Self {
+        self.transport(Arc::new(client))
+    }
+
+    /// Use a preconfigured http client for both the oauth and the api requests
+    ///
+    /// It can be used to configure the tls, proxies or connection pools. The
+    /// options of this builder that configure the http client, like `compression`,
+    /// are ignored. You need to call this methode before get_token and config_client
+    pub fn http_client(&mut self, client: reqwest::Client) -> &mut Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// A reqwest client builder with the options of this builder
+    fn http_client_builder(&self) -> reqwest::ClientBuilder {
+        let mut builder = reqwest::Client::builder()
+            .gzip(self.compression)
+            .deflate(self.compression)
+            .brotli(self.compression);
+        if let Some(timeout) = self.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        if self.no_proxy {
+            builder = builder.no_proxy();
+        }
+        builder
+    }
+
+    /// Send the requests with a `reqwest::blocking` client, unless a transport is set
+    ///
+    /// The client is built with the options of this builder, like `http_client_builder`.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn blocking_transport(&mut self) -> Result<&mut Self> {
+        if self.transport.is_some() {
+            return Ok(self);
+        }
+
+        let mut builder = reqwest::blocking::Client::builder()
+            .gzip(self.compression)
+            .deflate(self.compression)
+            .brotli(self.compression)
+            // The blocking client has a default timeout, the async one doesn't
+            .timeout(self.timeout);
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        if self.no_proxy {
+            builder = builder.no_proxy();
+        }
+
+        let client = builder.build().map_err(Error::ReqwestErr)?;
+        self.transport = Some(Arc::new(BlockingTransport(client)));
+        Ok(self)
+    }
+
+    /// The transport used to fetch the tokens, the token client unless a transport is set
+    fn token_transport(&mut self) -> Result<SharedTransport> {
+        match &self.transport {
+            Some(transport) => Ok(transport.clone()),
+            None => Ok(Arc::new(self.token_client()?)),
+        }
+    }
+
+    /// The client used to fetch the tokens, created on first use
+    fn token_client(&mut self) -> Result<reqwest::Client> {
+        if let Some(client) = self.token_client.as_ref().or(self.http_client.as_ref()) {
+            return Ok(client.clone());
+        }
+
+        let client = self
+            .http_client_builder()
+            .build()
+            .map_err(Error::ReqwestErr)?;
+        self.token_client = Some(client.clone());
+        Ok(client)
+    }
+
+    pub(crate) fn stored_token(&self) -> StoredToken {
+        StoredToken {
+            access_token: self
+                .access_token
+                .as_ref()
+                .map_or("", Secret::expose_secret)
+                .into(),
+            refresh_token: self
+                .refresh_token
+                .as_ref()
+                .map_or("", Secret::expose_secret)
+                .into(),
+            expires_at: self.token_outdated_after.unwrap_or(DateTime::UNIX_EPOCH),
+        }
+    }
+
+    /// Same as `set_url` but take and return the builder by value
+    ///
+    /// The `with_*` methods can be used to keep a partially configured builder
+    /// around and configure it conditionally.
+    ///
+    /// ```rust
+    /// # use helloasso::{HelloAsso, Error};
+    /// # fn main() -> Result<(), Error> {
+    /// # let sandbox = true;
+    /// let builder = HelloAsso::builder("client_id".to_string(), "client_secret".to_string());
+    /// let builder = if sandbox {
+    ///     builder.with_url(
+    ///         "https://api.helloasso-sandbox.com/v5",
+    ///         "https://api.helloasso-sandbox.com/oauth2/token",
+    ///     )?
+    /// } else {
+    ///     builder
+    /// };
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_url(mut self, url: &str, token_url: &str) -> Result<Self> {
+        self.set_url(url, token_url)?;
+        Ok(self)
+    }
+
+    /// Same as `base_url` but take and return the builder by value
+    pub fn with_base_url(mut self, base_url: &str) -> Result<Self> {
+        self.base_url(base_url)?;
+        Ok(self)
+    }
+
+    /// Same as `environment` but take and return the builder by value
+    pub fn with_environment(mut self, environment: Environment) -> Self {
+        self.environment(environment);
+        self
+    }
+
+    /// Same as `get_token` but take and return the builder by value
+    pub async fn with_token(mut self) -> Result<Self> {
+        self.get_token().await?;
+        Ok(self)
+    }
+
+    /// Same as `tokens` but take and return the builder by value
+    pub fn with_tokens(
+        mut self,
+        access_token: String,
+        refresh_token: String,
+        expires_at: impl Into<DateTime<Utc>>,
+    ) -> Self {
+        self.tokens(access_token, refresh_token, expires_at);
+        self
+    }
+
+    /// Same as `on_retry` but take and return the builder by value
+    pub fn with_on_retry(mut self, hook: impl Fn(&RetryEvent) + Send + Sync + 'static) -> Self {
+        self.on_retry(hook);
+        self
+    }
+
+    /// Same as `on_token_rotated` but take and return the builder by value
+    pub fn with_on_token_rotated(
+        mut self,
+        hook: impl Fn(&StoredToken) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_token_rotated(hook);
+        self
+    }
+
+    /// Same as `interceptor` but take and return the builder by value
+    pub fn with_interceptor(mut self, interceptor: impl Interceptor + 'static) -> Self {
+        self.interceptor(interceptor);
+        self
+    }
+
+    /// Same as `compression` but take and return the builder by value
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        self.compression(enabled);
+        self
+    }
+
+    /// Same as `decode_mode` but take and return the builder by value
+    pub fn with_decode_mode(mut self, mode: DecodeMode) -> Self {
+        self.decode_mode(mode);
+        self
+    }
+
+    /// Same as `reauthenticate_on_unauthorized` but take and return the builder by value
+    pub fn with_reauthenticate_on_unauthorized(mut self, enabled: bool) -> Self {
+        self.reauthenticate_on_unauthorized(enabled);
+        self
+    }
+
+    /// Same as `timeout` but take and return the builder by value
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout(timeout);
+        self
+    }
+
+    /// Same as `connect_timeout` but take and return the builder by value
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout(timeout);
+        self
+    }
+
+    /// Same as `proxy` but take and return the builder by value
+    pub fn with_proxy(mut self, url: &str) -> Result<Self> {
+        self.proxy(url)?;
+        Ok(self)
+    }
+
+    /// Same as `no_proxy` but take and return the builder by value
+    pub fn with_no_proxy(mut self) -> Self {
+        self.no_proxy();
+        self
+    }
+
+    /// Same as `refresh_margin` but take and return the builder by value
+    pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin(margin);
+        self
+    }
+
+    /// Same as `refresh_jitter` but take and return the builder by value
+    pub fn with_refresh_jitter(mut self, jitter: Duration) -> Self {
+        self.refresh_jitter(jitter);
+        self
+    }
+
+    /// Same as `rate_limit` but take and return the builder by value
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit(limit);
+        self
+    }
+
+    /// Same as `retry_policy` but take and return the builder by value
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy(policy);
+        self
+    }
+
+    /// Same as `transport` but take and return the builder by value
+    pub fn with_transport(mut self, transport: Arc<dyn HttpTransport>) -> Self {
+        self.transport(transport);
+        self
+    }
+
+    /// Same as `middleware_client` but take and return the builder by value
+    #[cfg(feature = "reqwest-middleware")]
+    pub fn with_middleware_client(
+        mut self,
+        client: reqwest_middleware::ClientWithMiddleware,
+    ) -> Self {
+        self.middleware_client(client);
+        self
+    }
+
+    /// Same as `http_client` but take and return the builder by value
+    pub fn with_http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client(client);
+        self
+    }
+
+    /// Same as `config_client` but take and return the builder by value
+    pub fn with_configured_client(mut self) -> Result<Self> {
+        self.config_client()?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{env_var, handle_response, HelloAssoBuilder, DEFAULT_ENVIRONMENT};
+    use crate::DecodeMode;
+    use crate::{
+        logging::{log_info, log_warn},
+        test_util::{self, MockApi},
+        Environment, Error, HelloAsso, Paginated, Payment, RetryEvent, RetryPolicy, Session,
+        StoredToken,
+    };
+    use chrono::{DateTime, Utc};
+    use dotenv::dotenv;
+    use reqwest::{Response, StatusCode};
+    use std::{
+        env,
+        io::{Read, Write},
+        net::TcpListener,
+        sync::{Arc, Mutex},
+        thread,
+        time::{Duration, SystemTime},
+    };
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, ResponseTemplate,
+    };
+
+    pub fn get_env_variables() -> (String, String) {
+        if let Err(err) = dotenv() {
+            log_warn!("Can't load .env file, {}", err);
+        } else {
+            log_info!(".env file loaded");
+        }
+
+        let client_id = env::var("CLIENT_ID").unwrap();
+        let client_secret = env::var("CLIENT_SECRET").unwrap();
+
+        (client_id, client_secret)
+    }
+
+    #[tokio::test]
+    async fn new_client() {
+        let api = MockApi::start().await;
+
+        let client = api.client().await.expect("Test failed");
+
+        assert_eq!(client.stored_token().access_token, test_util::ACCESS_TOKEN);
+    }
+
+    #[tokio::test]
+    async fn invalid_client_id() {
+        let api = MockApi::start().await;
+
+        let client = HelloAsso::builder("abc".to_string(), test_util::CLIENT_SECRET.to_string())
+            .with_base_url(&api.uri())
+            .unwrap()
+            .get_token()
+            .await
+            .map(|_| ());
+
+        assert!(matches!(client, Err(Error::AuthErr(_))))
+    }
+
+    #[tokio::test]
+    async fn invalid_client_secret() {
+        let api = MockApi::start().await;
+
+        let client = HelloAsso::builder(test_util::CLIENT_ID.to_string(), "abc".to_string())
+            .with_base_url(&api.uri())
+            .unwrap()
+            .get_token()
+            .await
+            .map(|_| ());
+
+        assert!(matches!(client, Err(Error::AuthErr(_))))
+    }
+
+    #[tokio::test]
+    async fn refresh_token() {
+        let api = MockApi::start().await;
+        let client = api.client().await.expect("Can't create the client");
+
+        client
+            .refresh_token()
+            .await
+            .expect("Could not refresh token");
+
+        let requests = api.server().received_requests().await.unwrap();
+        let body = String::from_utf8_lossy(&requests.last().unwrap().body).to_string();
+        assert!(body.contains("grant_type=refresh_token"));
+    }
+
+    #[tokio::test]
+    async fn refresh_revoked_token() {
+        let api = MockApi::start().await;
+        let client = HelloAsso::builder(
+            test_util::CLIENT_ID.to_string(),
+            test_util::CLIENT_SECRET.to_string(),
+        )
+        .with_base_url(&api.uri())
+        .unwrap()
+        .with_tokens("outdated".to_string(), "revoked".to_string(), Utc::now())
+        .with_configured_client()
+        .unwrap()
+        .build();
+
+        let refresh = client.refresh_token().await;
+
+        assert!(
+            matches!(&refresh, Err(Error::AuthErr(error)) if error.error == "unauthorized_client"),
+            "{refresh:?}"
+        );
+    }
+
+    /// Those tests use the sandbox, run them with `cargo test -- --ignored`
+    /// once `CLIENT_ID` and `CLIENT_SECRET` are set, see `env.example`
+    mod live {
+        use super::get_env_variables;
+        use crate::{Environment, Error, HelloAsso};
+
+        #[tokio::test]
+        #[ignore = "needs sandbox credentials"]
+        async fn new_client() {
+            let (client_id, client_secret) = get_env_variables();
+
+            HelloAsso::builder(client_id, client_secret)
+                .with_environment(Environment::Sandbox)
+                .get_token()
+                .await
+                .expect("Test failed");
+        }
+
+        #[tokio::test]
+        #[ignore = "needs sandbox credentials"]
+        async fn invalid_client_secret() {
+            let (client_id, _) = get_env_variables();
+
+            let client = HelloAsso::new(client_id, "abc".to_string()).await;
+
+            assert!(matches!(client, Err(Error::AuthErr(_))))
+        }
+
+        #[tokio::test]
+        #[ignore = "needs sandbox credentials"]
+        async fn refresh_token() {
+            let (client_id, client_secret) = get_env_variables();
+
+            let client = HelloAsso::new(client_id, client_secret)
+                .await
+                .expect("Can't create the client");
+
+            client
+                .refresh_token()
+                .await
+                .expect("Could not refresh token");
+        }
+    }
+
+    #[test]
+    fn client_is_shareable() {
+        fn assert_shareable<T: Clone + Send + Sync + 'static>() {}
+        assert_shareable::<HelloAsso>();
+    }
+
+    #[tokio::test]
+    async fn refresh_from_another_task() {
+        let api = MockApi::start().await;
+        let client = api.client().await.unwrap();
+        let expires_at = client.stored_token().expires_at;
+
+        let clone = client.clone();
+        tokio::spawn(async move { clone.refresh_token().await.map(|_| ()) })
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(client.stored_token().expires_at > expires_at);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn auto_refresh() {
+        let api = MockApi::start().await;
+        let client = HelloAsso::builder(
+            test_util::CLIENT_ID.to_string(),
+            test_util::CLIENT_SECRET.to_string(),
+        )
+        .with_base_url(&api.uri())
+        .unwrap()
+        .with_tokens(
+            "outdated".to_string(),
+            test_util::REFRESH_TOKEN.to_string(),
+            Utc::now() + Duration::from_secs(30),
+        )
+        .with_configured_client()
+        .unwrap()
+        .build();
+
+        let task = client.spawn_auto_refresh();
+        for _ in 0..50 {
+            if !client.needs_refresh() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        task.abort();
+
+        assert_eq!(client.stored_token().access_token, test_util::ACCESS_TOKEN);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn auto_refresh_stops_with_client() {
+        let api = MockApi::start().await;
+        let client = api.client().await.unwrap();
+
+        let task = client.spawn_auto_refresh();
+        drop(client);
+
+        tokio::time::timeout(Duration::from_secs(1), task)
+            .await
+            .expect("The task stops once the client is dropped")
+            .unwrap();
+    }
+
+    fn revoked_token_client(api: &MockApi) -> HelloAssoBuilder {
+        HelloAsso::builder(
+            test_util::CLIENT_ID.to_string(),
+            test_util::CLIENT_SECRET.to_string(),
+        )
+        .with_base_url(&api.uri())
+        .unwrap()
+        .with_tokens(
+            "revoked".to_string(),
+            test_util::REFRESH_TOKEN.to_string(),
+            Utc::now() + Duration::from_secs(3600),
+        )
+        .with_configured_client()
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn reauthenticate_on_unauthorized() {
+        let api = MockApi::start().await;
+        let client = revoked_token_client(&api).build();
+
+        let payment = client.get_payment(test_util::PAYMENT_ID).await.unwrap();
+
+        assert_eq!(payment.id, test_util::PAYMENT_ID);
+        assert_eq!(client.stored_token().access_token, test_util::ACCESS_TOKEN);
+    }
+
+    #[tokio::test]
+    async fn unauthorized_without_reauthentication() {
+        let api = MockApi::start().await;
+        let client = revoked_token_client(&api)
+            .with_reauthenticate_on_unauthorized(false)
+            .build();
+
+        let payment = client.get_payment(test_util::PAYMENT_ID).await;
+
+        assert!(
+            matches!(payment, Err(Error::AuthErr(err)) if err.error_description.contains("denied"))
+        );
+    }
+
+    #[test]
+    fn debug_redacts_secrets() {
+        let builder = HelloAsso::builder("id".to_string(), "client-secret".to_string())
+            .with_tokens(
+                "access-token".to_string(),
+                "refresh-token".to_string(),
+                Utc::now(),
+            );
+        let builder_debug = format!("{builder:?}");
+        let client_debug = format!("{:?}", builder.with_configured_client().unwrap().build());
+
+        for debug in [builder_debug, client_debug] {
+            assert!(debug.contains("client_secret: \"***\""));
+            for secret in ["client-secret", "access-token", "refresh-token"] {
+                assert!(!debug.contains(secret), "{secret} leaked in {debug}");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn ping_invalid_url() {
+        let ping = HelloAsso::ping(Some("not an url")).await;
+
+        assert!(matches!(ping, Err(Error::ParseUrlErr(_))))
+    }
+
+    #[test]
+    fn raw_request_url() {
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string()).build();
+
+        let url = client.raw_endpoint("/organizations/my-asso/forms");
+        let request = client
+            .client
+            .get(url)
+            .query(&[("userSearchKey", "john doe&co")])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            request.url().as_str(),
+            "https://api.helloasso-sandbox.com/v5/organizations/my-asso/forms?userSearchKey=john+doe%26co"
+        );
+    }
+
+    #[test]
+    fn token_rotated_hook() {
+        let rotated = Arc::new(Mutex::new(Vec::new()));
+        let hook_rotated = rotated.clone();
+
+        let mut builder = HelloAsso::builder("abc".to_string(), "abc".to_string());
+        builder.on_token_rotated(move |token| hook_rotated.lock().unwrap().push(token.clone()));
+        let client = builder.build();
+
+        let token = StoredToken {
+            access_token: "access".into(),
+            refresh_token: "refresh".into(),
+            expires_at: DateTime::UNIX_EPOCH,
+        };
+        client.on_token_rotated.as_ref().unwrap().call(&token);
+
+        assert_eq!(*rotated.lock().unwrap(), vec![token]);
+    }
+
+    #[test]
+    fn compression_enabled_by_default() {
+        let builder = HelloAsso::builder("abc".to_string(), "abc".to_string());
+        assert!(builder.compression);
+
+        let builder = builder.with_compression(false);
+        assert!(!builder.compression);
+    }
+
+    #[tokio::test]
+    async fn no_content_response() {
+        let response = http::Response::builder()
+            .status(StatusCode::NO_CONTENT)
+            .body("")
+            .unwrap();
+
+        let result = handle_response::<()>(Response::from(response), DecodeMode::Lenient).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn empty_ok_response() {
+        let response = Response::from(http::Response::new(""));
+
+        assert!(handle_response::<()>(response, DecodeMode::Lenient)
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn decode_error_path() {
+        let response = Response::from(http::Response::new(
+            r#"{ "data": [{ "id": 1, "amount": 100 }, { "id": 2, "amount": "100" }] }"#,
+        ));
+
+        let result = handle_response::<Paginated<Payment>>(response, DecodeMode::Lenient).await;
+
+        let Err(Error::DecodeErr(err)) = result else {
+            panic!("Expected a decode error, got {result:?}");
+        };
+        assert_eq!(err.path().to_string(), "data[1].amount");
+    }
+
+    #[test]
+    fn needs_refresh_within_margin() {
+        let mut builder = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_refresh_jitter(Duration::from_secs(60));
+
+        builder.token_outdated_after = Some(Utc::now() + Duration::from_secs(3600));
+        assert!(!builder.build().needs_refresh());
+
+        builder.token_outdated_after = Some(Utc::now() + Duration::from_secs(30));
+        assert!(builder.build().needs_refresh());
+    }
+
+    #[test]
+    fn configurable_refresh_margin() {
+        let mut builder = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_refresh_margin(Duration::from_secs(600));
+
+        builder.token_outdated_after = Some(Utc::now() + Duration::from_secs(300));
+        assert!(builder.build().needs_refresh());
+    }
+
+    #[test]
+    fn token_expiry() {
+        let mut builder = HelloAsso::builder("abc".to_string(), "abc".to_string());
+        let expires_at = Utc::now() + Duration::from_secs(300);
+        builder.token_outdated_after = Some(expires_at);
+        let client = builder.build();
+
+        assert_eq!(client.token_expires_at(), expires_at);
+        assert!(client.token_expires_in() <= Duration::from_secs(300));
+        assert!(client.token_expires_in() > Duration::from_secs(200));
+        assert!(client.is_token_valid(Duration::from_secs(60)));
+        assert!(!client.is_token_valid(Duration::from_secs(600)));
+
+        builder.token_outdated_after = Some(Utc::now() - Duration::from_secs(10));
+        let client = builder.build();
+
+        assert_eq!(client.token_expires_in(), Duration::ZERO);
+        assert!(!client.is_token_valid(Duration::ZERO));
+    }
+
+    #[tokio::test]
+    async fn refresh_before_request() {
+        let mut builder = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_url("http://127.0.0.1:1/v5", "http://127.0.0.1:1/oauth2/token")
+            .unwrap();
+        builder.refresh_token = Some("refresh".to_string().into());
+        builder.token_outdated_after = Some(Utc::now());
+        let client = builder.build();
+
+        let organization = client.get_organization("my-asso").await;
+
+        // The unreachable token url is requested before the api
+        let Err(Error::ReqwestErr(err)) = organization else {
+            panic!("Expected a request error, got {organization:?}");
+        };
+        assert_eq!(err.url().map(|url| url.path()), Some("/oauth2/token"));
+    }
+
+    #[tokio::test]
+    async fn refresh_restored_tokens_with_http_client() {
+        let api = MockApi::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth2/token"))
+            .and(header("x-app", "export"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(test_util::token()))
+            .with_priority(1)
+            .expect(1)
+            .mount(api.server())
+            .await;
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert("x-app", "export".parse().unwrap());
+        let client = HelloAsso::builder(
+            test_util::CLIENT_ID.to_string(),
+            test_util::CLIENT_SECRET.to_string(),
+        )
+        .with_base_url(&api.uri())
+        .unwrap()
+        .with_http_client(
+            reqwest::Client::builder()
+                .default_headers(headers)
+                .build()
+                .unwrap(),
+        )
+        .with_tokens(
+            "expired".to_string(),
+            test_util::REFRESH_TOKEN.to_string(),
+            Utc::now(),
+        )
+        .with_configured_client()
+        .unwrap()
+        .build();
+
+        client.refresh_token().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn refresh_restored_tokens_timeout() {
+        let api = MockApi::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth2/token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(test_util::token())
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .with_priority(1)
+            .mount(api.server())
+            .await;
+        let client = HelloAsso::builder(
+            test_util::CLIENT_ID.to_string(),
+            test_util::CLIENT_SECRET.to_string(),
+        )
+        .with_base_url(&api.uri())
+        .unwrap()
+        .with_timeout(Duration::from_millis(200))
+        .with_tokens(
+            "expired".to_string(),
+            test_util::REFRESH_TOKEN.to_string(),
+            Utc::now(),
+        )
+        .with_configured_client()
+        .unwrap()
+        .build();
+
+        let start = SystemTime::now();
+        assert!(client.refresh_token().await.is_err());
+        assert!(start.elapsed().unwrap() < Duration::from_secs(2));
+    }
+
+    #[tokio::test]
+    async fn proxy_restored_tokens() {
+        let api = MockApi::start().await;
+        let client = HelloAsso::builder(
+            test_util::CLIENT_ID.to_string(),
+            test_util::CLIENT_SECRET.to_string(),
+        )
+        .with_base_url("http://helloasso.invalid")
+        .unwrap()
+        .with_proxy(&api.uri())
+        .unwrap()
+        .with_tokens(
+            "expired".to_string(),
+            test_util::REFRESH_TOKEN.to_string(),
+            Utc::now(),
+        )
+        .with_configured_client()
+        .unwrap()
+        .build();
+
+        // Both the refresh and the request go through the proxy
+        client.refresh_token().await.unwrap();
+        client
+            .get_organization(test_util::ORGANIZATION_SLUG)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn flush_tokens() {
+        let flushed = Arc::new(Mutex::new(None));
+        let hook_flushed = flushed.clone();
+
+        let mut builder = HelloAsso::builder("abc".to_string(), "abc".to_string());
+        builder.access_token = Some("access".to_string().into());
+        builder.refresh_token = Some("refresh".to_string().into());
+        let client = builder
+            .with_on_token_rotated(move |token| *hook_flushed.lock().unwrap() = Some(token.clone()))
+            .build();
+
+        client.flush().await.unwrap();
+
+        assert_eq!(*flushed.lock().unwrap(), Some(client.stored_token()));
+    }
+
+    #[tokio::test]
+    async fn flush_during_refresh() {
+        let api = MockApi::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth2/token"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(test_util::token())
+                    .set_delay(Duration::from_millis(300)),
+            )
+            .with_priority(1)
+            .mount(api.server())
+            .await;
+        let flushed = Arc::new(Mutex::new(Vec::new()));
+        let hook_flushed = flushed.clone();
+        let client = HelloAsso::builder(
+            test_util::CLIENT_ID.to_string(),
+            test_util::CLIENT_SECRET.to_string(),
+        )
+        .with_base_url(&api.uri())
+        .unwrap()
+        .with_tokens(
+            "outdated".to_string(),
+            test_util::REFRESH_TOKEN.to_string(),
+            Utc::now(),
+        )
+        .with_configured_client()
+        .unwrap()
+        .with_on_token_rotated(move |token| hook_flushed.lock().unwrap().push(token.clone()))
+        .build();
+
+        let clone = client.clone();
+        let refresh = tokio::spawn(async move { clone.refresh_token().await.map(|_| ()) });
+        // Wait for the refresh to be in progress
+        while client.refresh_lock.try_lock().is_some() {
+            tokio::task::yield_now().await;
+        }
+        client.flush().await.unwrap();
+
+        let flushed = flushed.lock().unwrap().clone();
+        assert_eq!(flushed.len(), 2);
+        assert!(flushed
+            .iter()
+            .all(|token| token.access_token == test_util::ACCESS_TOKEN));
+        refresh.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn restore_session() {
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_environment(Environment::Production)
+            .with_tokens(
+                "access".to_string(),
+                "refresh".to_string(),
+                Utc::now() + Duration::from_secs(1800),
+            )
+            .with_configured_client()
+            .unwrap()
+            .build();
+        let session = client.session();
+
+        let client = HelloAsso::from_session("abc".to_string(), session.clone()).unwrap();
+
+        assert_eq!(client.session(), session);
+        // The tests default to the sandbox
+        assert_eq!(client.url.as_str(), Environment::Production.api_url());
+        assert_eq!(
+            client.token_url.as_str(),
+            Environment::Production.token_url()
+        );
+        assert!(!client.needs_refresh());
+    }
+
+    #[test]
+    fn restore_session_without_urls() {
+        // A session saved before the urls were part of it
+        let session: Session = serde_json::from_value(serde_json::json!({
+            "access_token": "access",
+            "refresh_token": "refresh",
+            "expires_at": "2023-06-01T10:00:00Z"
+        }))
+        .unwrap();
+
+        let client = HelloAsso::from_session("abc".to_string(), session).unwrap();
+
+        assert_eq!(client.url.as_str(), DEFAULT_ENVIRONMENT.api_url());
+        assert_eq!(client.stored_token().access_token, "access");
+    }
+
+    #[test]
+    fn session_expiry_as_timestamp() {
+        let session = Session {
+            token: StoredToken {
+                access_token: "access".into(),
+                refresh_token: "refresh".into(),
+                expires_at: "2023-06-01T10:00:00Z".parse().unwrap(),
+            },
+            api_url: None,
+            token_url: None,
+            authorize_url: None,
+        };
+
+        let saved = serde_json::to_value(&session).unwrap();
+
+        assert_eq!(saved["expires_at"], "2023-06-01T10:00:00Z");
+    }
+
+    #[test]
+    fn builder_with_tokens() {
+        // A SystemTime is accepted as well as a DateTime
+        let expires_at = SystemTime::now() + Duration::from_secs(1800);
+
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_tokens("access".to_string(), "refresh".to_string(), expires_at)
+            .with_configured_client()
+            .unwrap()
+            .build();
+
+        assert_eq!(
+            client.stored_token(),
+            StoredToken {
+                access_token: "access".into(),
+                refresh_token: "refresh".into(),
+                expires_at: expires_at.into()
+            }
+        );
+    }
+
+    /// Serve the raw http `responses`, one connection per response, and return the server url
+    fn serve(responses: &'static [&'static str]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let _ = stream.read(&mut [0; 4096]).unwrap();
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        url
+    }
+
+    #[tokio::test]
+    async fn custom_http_client() {
+        // An https only client can't reach the local http url, whatever the request
+        let http_client = reqwest::Client::builder().https_only(true).build().unwrap();
+        let mut builder = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_base_url("http://127.0.0.1:1")
+            .unwrap()
+            .with_http_client(http_client);
+
+        let token = builder.get_token().await;
+        assert!(matches!(token, Err(Error::ReqwestErr(err)) if err.is_builder()));
+
+        let client = builder
+            .with_tokens(
+                "access".to_string(),
+                "refresh".to_string(),
+                Utc::now() + Duration::from_secs(3600),
+            )
+            .with_configured_client()
+            .unwrap()
+            .build();
+        let payment = client.get_payment(42).await;
+        assert!(matches!(payment, Err(Error::ReqwestErr(err)) if err.is_builder()));
+    }
+
+    #[tokio::test]
+    async fn request_timeout() {
+        // The connection is accepted by the system but the api never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_base_url(&url)
+            .unwrap()
+            .with_timeout(Duration::from_millis(50))
+            .with_configured_client()
+            .unwrap()
+            .build();
+
+        let payment = client.get_payment(42).await;
+
+        assert!(matches!(payment, Err(Error::ReqwestErr(err)) if err.is_timeout()));
+    }
+
+    #[tokio::test]
+    async fn requests_through_proxy() {
+        let proxy = serve(&[
+            "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: 23\r\n\r\n{\"id\":42,\"amount\":1250}",
+        ]);
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_base_url("http://api.helloasso.invalid")
+            .unwrap()
+            .with_proxy(&proxy)
+            .unwrap()
+            .with_configured_client()
+            .unwrap()
+            .build();
+
+        let payment = client.get_payment(42).await.unwrap();
+
+        assert_eq!(payment.id, 42);
+    }
+
+    #[tokio::test]
+    async fn retry_transient_failure() {
+        let url = serve(&[
+            "HTTP/1.1 503 Service Unavailable\r\nconnection: close\r\ncontent-length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: 2\r\n\r\n{}",
+        ]);
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_base_url(&url)
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                base_delay: Duration::from_millis(1),
+                ..Default::default()
+            })
+            .with_configured_client()
+            .unwrap()
+            .build();
+
+        let response = client.get_raw("organizations/my-asso", &[]).await;
+
+        assert_eq!(response.unwrap(), serde_json::json!({}));
+    }
+
+    #[tokio::test]
+    async fn unexpected_status() {
+        let response = http::Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .header("x-request-id", "0HMV8")
+            .body("upstream failure")
+            .unwrap();
+
+        let result = handle_response::<()>(Response::from(response), DecodeMode::Lenient).await;
+
+        let Err(Error::UnexpectedStatus {
+            status,
+            error,
+            body,
+            meta,
+        }) = result
+        else {
+            panic!("Expected an unexpected status error, got {result:?}");
+        };
+        assert_eq!(status, StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(error, None);
+        assert_eq!(body, "upstream failure");
+        assert_eq!(meta.unwrap().request_id.as_deref(), Some("0HMV8"));
+    }
+
+    #[tokio::test]
+    async fn bad_request() {
+        let response = http::Response::builder()
+            .status(StatusCode::BAD_REQUEST)
+            .header("x-request-id", "0HMV8")
+            .body(r#"{ "errors": [{ "code": "InvalidField", "message": "must be positive", "field": "totalAmount" }] }"#)
+            .unwrap();
+
+        let result = handle_response::<()>(Response::from(response), DecodeMode::Lenient).await;
+
+        let Err(Error::BadRequest {
+            status,
+            error,
+            body,
+            meta,
+        }) = result
+        else {
+            panic!("Expected a bad request error, got {result:?}");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.errors[0].field.as_deref(), Some("totalAmount"));
+        assert!(body.contains("must be positive"), "{body}");
+        assert_eq!(meta.unwrap().request_id.as_deref(), Some("0HMV8"));
+    }
+
+    #[tokio::test]
+    async fn forbidden() {
+        for (body, message) in [
+            (
+                r#"{ "message": "missing FormAdmin role" }"#,
+                "missing FormAdmin role",
+            ),
+            (
+                r#"{ "errors": [{ "message": "missing FormAdmin role" }] }"#,
+                "missing FormAdmin role",
+            ),
+            ("", ""),
+        ] {
+            let response = http::Response::builder()
+                .status(StatusCode::FORBIDDEN)
+                .body(body)
+                .unwrap();
+
+            let result = handle_response::<()>(Response::from(response), DecodeMode::Lenient).await;
+
+            assert!(
+                matches!(&result, Err(Error::PermErr(err)) if err.message == message),
+                "{result:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn rate_limited() {
+        let response = http::Response::builder()
+            .status(StatusCode::TOO_MANY_REQUESTS)
+            .header("retry-after", "2")
+            .body("")
+            .unwrap();
+
+        let result = handle_response::<()>(Response::from(response), DecodeMode::Lenient).await;
+
+        assert!(matches!(
+            result,
+            Err(Error::RateLimited {
+                retry_after: Some(delay),
+                ..
+            }) if delay == Duration::from_secs(2)
+        ));
+    }
+
+    #[tokio::test]
+    async fn retry_after_rate_limit() {
+        let url = serve(&[
+            "HTTP/1.1 429 Too Many Requests\r\nretry-after: 0\r\nconnection: close\r\ncontent-length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: 2\r\n\r\n{}",
+        ]);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let hook_events = events.clone();
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_base_url(&url)
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                base_delay: Duration::from_secs(60),
+                max_delay: Duration::from_secs(60),
+                ..Default::default()
+            })
+            .with_on_retry(move |event| hook_events.lock().unwrap().push(event.clone()))
+            .with_configured_client()
+            .unwrap()
+            .build();
+
+        let response = client.get_raw("organizations/my-asso", &[]).await;
+
+        assert_eq!(response.unwrap(), serde_json::json!({}));
+        assert_eq!(
+            *events.lock().unwrap(),
+            [RetryEvent {
+                attempt: 1,
+                max_attempts: 2,
+                delay: Duration::ZERO,
+                cause: "429 Too Many Requests".to_string(),
+                retry_after: Some(Duration::ZERO),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn retry_backoff_at_max_delay() {
+        let url = serve(&[
+            "HTTP/1.1 503 Service Unavailable\r\nconnection: close\r\ncontent-length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nconnection: close\r\ncontent-length: 2\r\n\r\n{}",
+        ]);
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_base_url(&url)
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                base_delay: Duration::from_millis(20),
+                max_delay: Duration::from_millis(10),
+                jitter: Duration::from_millis(10),
+            })
+            .with_configured_client()
+            .unwrap()
+            .build();
+
+        // The jitter is added to a backoff capped at the max delay, the request is still retried
+        let response = client.get_raw("organizations/my-asso", &[]).await;
+
+        assert_eq!(response.unwrap(), serde_json::json!({}));
+    }
+
+    #[tokio::test]
+    async fn retry_after_above_max_delay() {
+        let url = serve(&[
+            "HTTP/1.1 429 Too Many Requests\r\nretry-after: 120\r\nconnection: close\r\ncontent-length: 0\r\n\r\n",
+        ]);
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_base_url(&url)
+            .unwrap()
+            .with_retry_policy(RetryPolicy {
+                max_attempts: 2,
+                ..Default::default()
+            })
+            .with_configured_client()
+            .unwrap()
+            .build();
+
+        let response = client.get_raw("organizations/my-asso", &[]).await;
+
+        assert!(matches!(
+            response,
+            Err(Error::RateLimited {
+                retry_after: Some(delay),
+                ..
+            }) if delay == Duration::from_secs(120)
+        ));
+    }
+
+    #[test]
+    fn missing_env_var() {
+        let var = env_var("HELLOASSO_TEST_MISSING_VARIABLE");
+
+        assert!(matches!(
+            var,
+            Err(Error::MissingEnv {
+                var: "HELLOASSO_TEST_MISSING_VARIABLE"
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_environment() {
+        assert_eq!(
+            "sandbox".parse::<Environment>().unwrap(),
+            Environment::Sandbox
+        );
+        assert_eq!(
+            "Production".parse::<Environment>().unwrap(),
+            Environment::Production
+        );
+        assert!(matches!(
+            "staging".parse::<Environment>(),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn owned_builder() {
+        let builder = HelloAsso::builder("abc".to_string(), "abc".to_string());
+        let builder = builder
+            .with_url("https://example.com/v5", "https://example.com/oauth2/token")
+            .expect("Valid urls");
+
+        assert_eq!(builder.url.as_str(), "https://example.com/v5");
+
+        let builder = builder.with_url("not an url", "https://example.com/oauth2/token");
+
+        assert!(matches!(builder, Err(Error::ParseUrlErr(_))))
+    }
+
+    #[test]
+    fn base_url() {
+        let builder = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_base_url("http://127.0.0.1:8080/")
+            .unwrap();
+
+        assert_eq!(builder.url.as_str(), "http://127.0.0.1:8080/v5");
+        assert_eq!(
+            builder.token_url.as_str(),
+            "http://127.0.0.1:8080/oauth2/token"
+        );
+    }
+
+    #[test]
+    fn environment_urls() {
+        let builder = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_environment(Environment::Production);
+
+        assert_eq!(builder.url.as_str(), "https://api.helloasso.com/v5");
+        assert_eq!(
+            builder.token_url.as_str(),
+            "https://api.helloasso.com/oauth2/token"
+        );
+
+        let builder = builder.with_environment(Environment::Sandbox);
+
+        assert_eq!(builder.url.as_str(), "https://api.helloasso-sandbox.com/v5");
     }
 }
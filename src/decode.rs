@@ -0,0 +1,140 @@
+//! `decode` the decoding of the api responses
+//!
+//! The responses are decoded leniently by default, the api evolves and a new field or
+//! enum value shouldn't break the client. The strict mode rejects them instead, it is
+//! meant to spot the changes of the api, ie in CI.
+//!
+//! A missing required field is an error in both modes, the api omits the optional
+//! fields without value so a missing optional field is `None` in both modes.
+
+use std::cell::Cell;
+
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::{Error, Result};
+
+/// How the responses of the api are decoded, see `decode_mode` on the builder
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DecodeMode {
+    /// Unknown fields are ignored, unknown enum values are kept as `Other`
+    #[default]
+    Lenient,
+    /// Unknown fields and enum values are errors, missing optional fields are still `None`
+    Strict,
+}
+
+thread_local! {
+    /// The mode of the decoding in progress, read by the enums meeting an unknown value
+    static MODE: Cell<DecodeMode> = const { Cell::new(DecodeMode::Lenient) };
+}
+
+/// Decode json, the error tells which field failed
+pub(crate) fn decode_json<T: DeserializeOwned>(body: &[u8], mode: DecodeMode) -> Result<T> {
+    // The decoding is synchronous, the mode can't leak to another decoding
+    let previous = MODE.replace(mode);
+    let result = decode_with_mode(body, mode);
+    MODE.set(previous);
+    result
+}
+
+fn decode_with_mode<T: DeserializeOwned>(body: &[u8], mode: DecodeMode) -> Result<T> {
+    let deserializer = &mut serde_json::Deserializer::from_slice(body);
+    let mut unknown_field = None;
+    let mut on_ignored = |path: serde_ignored::Path| {
+        unknown_field.get_or_insert_with(|| path.to_string());
+    };
+
+    let value = serde_path_to_error::deserialize(serde_ignored::Deserializer::new(
+        deserializer,
+        &mut on_ignored,
+    ))
+    .map_err(Error::DecodeErr)?;
+
+    match unknown_field {
+        Some(path) if mode == DecodeMode::Strict => {
+            Err(Error::DecodeErr(serde_path_to_error::Error::new(
+                serde_path_to_error::Track::new().path(),
+                serde::de::Error::custom(format!("unknown field `{path}`")),
+            )))
+        }
+        _ => Ok(value),
+    }
+}
+
+/// Whether the decoding in progress is strict
+pub(crate) fn is_strict() -> bool {
+    MODE.get() == DecodeMode::Strict
+}
+
+/// The error of an unknown enum value in strict mode
+pub(crate) fn unknown_value(value: &str) -> String {
+    format!("unknown value `{value}`")
+}
+
+/// The name of an enum value, for the enums keeping unknown values as `Other`
+#[derive(Deserialize)]
+pub(crate) struct Name(pub String);
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_json, DecodeMode};
+    use crate::{Error, Organization, Payment, PaymentState};
+
+    #[test]
+    fn lenient_decoding() {
+        let payment: Payment = decode_json(
+            br#"{ "id": 42, "amount": 1250, "state": "NewState", "newField": true }"#,
+            DecodeMode::Lenient,
+        )
+        .unwrap();
+
+        assert_eq!(
+            payment.state,
+            Some(PaymentState::Other("NewState".to_string()))
+        );
+    }
+
+    #[test]
+    fn strict_decoding() {
+        let unknown_field = decode_json::<Payment>(
+            br#"{ "id": 42, "amount": 1250, "newField": true }"#,
+            DecodeMode::Strict,
+        );
+        assert!(
+            matches!(&unknown_field, Err(Error::DecodeErr(err)) if err.to_string().contains("newField")),
+            "{unknown_field:?}"
+        );
+
+        let unknown_value = decode_json::<Payment>(
+            br#"{ "id": 42, "amount": 1250, "state": "NewState" }"#,
+            DecodeMode::Strict,
+        );
+        assert!(
+            matches!(&unknown_value, Err(Error::DecodeErr(err)) if err.path().to_string() == "state"),
+            "{unknown_value:?}"
+        );
+
+        let other_means = decode_json::<Payment>(
+            br#"{ "id": 42, "amount": 1250, "paymentMeans": "Other" }"#,
+            DecodeMode::Strict,
+        );
+        assert!(other_means.is_ok(), "{other_means:?}");
+
+        let unknown_category = decode_json::<Organization>(
+            br#"{ "organizationSlug": "my-asso", "name": "My asso", "category": "Astronomy" }"#,
+            DecodeMode::Strict,
+        );
+        assert!(
+            matches!(&unknown_category, Err(Error::DecodeErr(err)) if err.to_string().contains("Astronomy")),
+            "{unknown_category:?}"
+        );
+    }
+
+    #[test]
+    fn unknown_value_serialized_by_name() {
+        assert_eq!(
+            serde_json::to_string(&PaymentState::Other("NewState".to_string())).unwrap(),
+            "\"NewState\""
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! `directory` search the public directory of organizations and forms
+
+use serde::Serialize;
+
+use crate::{
+    FormLightModel, FormType, HelloAsso, Organization, OrganizationCategory, Paginated,
+    PaginationParams, Result,
+};
+
+/// Search parameters of the organizations directory
+///
+/// Unset fields are not sent, the api default are used.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryOrgQuery {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cities: Vec<String>,
+    /// Zip codes the organizations are located in, the directory has no radius search
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub zip_codes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<OrganizationCategory>,
+    #[serde(rename = "sortField", skip_serializing_if = "Option::is_none")]
+    pub sort: Option<OrgSort>,
+}
+
+impl DirectoryOrgQuery {
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_city(mut self, city: impl Into<String>) -> Self {
+        self.cities.push(city.into());
+        self
+    }
+
+    pub fn with_zip_code(mut self, zip_code: impl Into<String>) -> Self {
+        self.zip_codes.push(zip_code.into());
+        self
+    }
+
+    pub fn with_category(mut self, category: OrganizationCategory) -> Self {
+        self.categories.push(category);
+        self
+    }
+
+    pub fn with_sort(mut self, sort: OrgSort) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+}
+
+/// Search parameters of the forms directory
+///
+/// Unset fields are not sent, the api default are used.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectoryFormsQuery {
+    #[serde(rename = "formName", skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(rename = "formCities", skip_serializing_if = "Vec::is_empty")]
+    pub cities: Vec<String>,
+    #[serde(rename = "formZipCodes", skip_serializing_if = "Vec::is_empty")]
+    pub zip_codes: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub form_types: Vec<FormType>,
+    /// Activity types, kept as strings because helloasso taxonomy evolve
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub activity_types: Vec<String>,
+    /// Categories of the organizations publishing the forms
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub organization_categories: Vec<OrganizationCategory>,
+    /// Public tags of the forms
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl DirectoryFormsQuery {
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn with_city(mut self, city: impl Into<String>) -> Self {
+        self.cities.push(city.into());
+        self
+    }
+
+    pub fn with_zip_code(mut self, zip_code: impl Into<String>) -> Self {
+        self.zip_codes.push(zip_code.into());
+        self
+    }
+
+    pub fn with_form_type(mut self, form_type: FormType) -> Self {
+        self.form_types.push(form_type);
+        self
+    }
+
+    pub fn with_activity_type(mut self, activity_type: impl Into<String>) -> Self {
+        self.activity_types.push(activity_type.into());
+        self
+    }
+
+    pub fn with_organization_category(mut self, category: OrganizationCategory) -> Self {
+        self.organization_categories.push(category);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+}
+
+/// Ordering of the organizations directory search results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum OrgSort {
+    Relevance,
+    Name,
+    CollectedAmount,
+}
+
+impl HelloAsso {
+    /// Search organizations in the public directory
+    ///
+    /// The search isn't scoped to an organization, a `404` names the directory.
+    pub async fn directory_search_organizations(
+        &self,
+        query: &DirectoryOrgQuery,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Organization>> {
+        let url = self.endpoint(&["directory", "organizations"]);
+        self.post(url, &pagination.query(), query)
+            .await
+            .map_err(|err| err.with_resource("directory", "organizations"))
+    }
+
+    /// Search forms in the public directory
+    pub async fn directory_search_forms(
+        &self,
+        query: &DirectoryFormsQuery,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<FormLightModel>> {
+        let url = self.endpoint(&["directory", "forms"]);
+        self.post(url, &pagination.query(), query)
+            .await
+            .map_err(|err| err.with_resource("directory", "forms"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_util::MockApi, DirectoryFormsQuery, DirectoryOrgQuery, Error, FormType, OrgSort,
+        OrganizationCategory, PaginationParams,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn default_query_is_empty() {
+        let query = DirectoryOrgQuery::default();
+
+        assert_eq!(serde_json::to_value(&query).unwrap(), json!({}));
+    }
+
+    #[test]
+    fn filters_query() {
+        let query = DirectoryOrgQuery {
+            zip_codes: vec!["75001".to_string(), "75002".to_string()],
+            categories: vec![
+                OrganizationCategory::Sport,
+                OrganizationCategory::Other("Astronomy".to_string()),
+            ],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_value(&query).unwrap(),
+            json!({ "zipCodes": ["75001", "75002"], "categories": ["Sport", "Astronomy"] })
+        );
+    }
+
+    #[test]
+    fn sorted_query() {
+        let query = DirectoryOrgQuery {
+            sort: Some(OrgSort::Name),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_value(&query).unwrap(),
+            json!({ "sortField": "Name" })
+        );
+    }
+
+    #[test]
+    fn forms_query_builder() {
+        let query = DirectoryFormsQuery::default()
+            .with_city("Lyon")
+            .with_form_type(FormType::Event)
+            .with_activity_type("Sport")
+            .with_organization_category(OrganizationCategory::Sport);
+
+        assert_eq!(
+            serde_json::to_value(&query).unwrap(),
+            json!({
+                "formCities": ["Lyon"],
+                "formTypes": ["Event"],
+                "activityTypes": ["Sport"],
+                "organizationCategories": ["Sport"]
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn directory_not_found() {
+        let api = MockApi::start().await;
+        let client = api.client().await.unwrap();
+        let pagination = PaginationParams::default();
+
+        let organizations = client
+            .directory_search_organizations(&DirectoryOrgQuery::default(), &pagination)
+            .await;
+        assert!(
+            matches!(&organizations, Err(Error::NotFound { resource: "directory", id, .. }) if id == "organizations"),
+            "{organizations:?}"
+        );
+
+        let forms = client
+            .directory_search_forms(&DirectoryFormsQuery::default(), &pagination)
+            .await;
+        assert!(
+            matches!(&forms, Err(Error::NotFound { resource: "directory", id, .. }) if id == "forms"),
+            "{forms:?}"
+        );
+    }
+}
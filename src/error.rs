@@ -1,27 +1,119 @@
 //! Errors this crate can return
 
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
 use thiserror::Error;
 
 use serde::Deserialize;
 
+use crate::ResponseMeta;
+
+/// Result type returned by the [client](crate::HelloAsso)
+pub type Result<T> = std::result::Result<T, Error>;
+
 /// Errors that may occur when using the [client](crate::HelloAsso)
 ///
 /// It can ether be a [Reqwest Error](reqwest::Error) or an [Authentication Error](crate::AuthenticationError)
 #[derive(Error, Debug)]
 #[non_exhaustive]
 pub enum Error {
+    /// The underlying error is the [source](std::error::Error::source) of this one
     #[error("request failed")]
     ReqwestErr(#[from] reqwest::Error),
-    #[error("authentification failed")]
+    /// A middleware of the [transport](crate::HttpTransport) failed
+    #[error("middleware failed")]
+    MiddlewareErr(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// The api refused the credentials, the message of the api is displayed
+    #[error("authentication failed: {0}")]
     AuthErr(AuthenticationError),
-    #[error("your don't have the right permission")]
+    /// The api refused the access, the message of the api is displayed
+    #[error("permission denied: {0}")]
     PermErr(AuthorizationError),
-    #[error("can't decode request")]
-    DecodeErr(reqwest::Error),
+    /// The response can't be decoded, the source tells which field failed, ie `data[3].payments[0].state`
+    #[error("can't decode response")]
+    DecodeErr(#[source] DecodeError),
     #[error("can't parse url")]
-    ParseUrlErr(url::ParseError),
+    ParseUrlErr(#[from] url::ParseError),
+    #[error("invalid input: {0}")]
+    InvalidInput(String),
+    #[error("missing environment variable {var}")]
+    MissingEnv { var: &'static str },
+    /// The api refused the request, ie a field is invalid
+    ///
+    /// `error` is the error sent by the api, the raw body when the body isn't one.
+    #[error("bad request: {error}")]
+    BadRequest {
+        status: reqwest::StatusCode,
+        error: ApiError,
+        body: String,
+        meta: Option<Box<ResponseMeta>>,
+    },
+    #[error("{resource} {id} not found")]
+    NotFound {
+        resource: &'static str,
+        id: String,
+        meta: Option<Box<ResponseMeta>>,
+    },
+    /// Too many requests were sent, `retry_after` is the delay asked by the api
+    #[error("rate limited by the api{}", display_retry_after(retry_after))]
+    RateLimited {
+        retry_after: Option<Duration>,
+        meta: Option<Box<ResponseMeta>>,
+    },
+    /// The api answered with a status this crate doesn't handle
+    ///
+    /// `error` is the error sent by the api, when the body is one.
+    #[error("unexpected status {status}: {}", display_api_error(error, body))]
+    UnexpectedStatus {
+        status: reqwest::StatusCode,
+        error: Option<ApiError>,
+        body: String,
+        meta: Option<Box<ResponseMeta>>,
+    },
+}
+
+/// A decoding error along with the path of the field that failed, see [path](serde_path_to_error::Error::path)
+pub type DecodeError = serde_path_to_error::Error<serde_json::Error>;
+
+fn display_retry_after(retry_after: &Option<Duration>) -> String {
+    retry_after
+        .map(|delay| format!(", retry after {delay:?}"))
+        .unwrap_or_default()
+}
+
+/// The error sent by the api, or the raw body when it isn't an error of the api
+fn display_api_error(error: &Option<ApiError>, body: &str) -> String {
+    match error {
+        Some(error) => error.to_string(),
+        None => body.to_string(),
+    }
+}
+
+impl Error {
+    /// The metadata of the response the error comes from, ie its request id for the helloasso support
+    pub fn response_meta(&self) -> Option<&ResponseMeta> {
+        match self {
+            Error::AuthErr(err) => err.meta.as_deref(),
+            Error::PermErr(err) => err.meta.as_deref(),
+            Error::BadRequest { meta, .. }
+            | Error::NotFound { meta, .. }
+            | Error::RateLimited { meta, .. }
+            | Error::UnexpectedStatus { meta, .. } => meta.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Name the resource of a [NotFound](crate::Error::NotFound) error, other errors are left untouched
+    pub(crate) fn with_resource(self, resource: &'static str, id: impl ToString) -> Self {
+        match self {
+            Error::NotFound { meta, .. } => Error::NotFound {
+                resource,
+                id: id.to_string(),
+                meta,
+            },
+            err => err,
+        }
+    }
 }
 
 /// Authentication Error that may occur when trying to access the api
@@ -35,6 +127,9 @@ pub enum Error {
 pub struct AuthenticationError {
     pub error: String,
     pub error_description: String,
+    /// The metadata of the response
+    #[serde(skip)]
+    pub meta: Option<Box<ResponseMeta>>,
 }
 
 impl Display for AuthenticationError {
@@ -47,6 +142,9 @@ impl Display for AuthenticationError {
 #[derive(Error, Debug, Deserialize)]
 pub struct AuthorizationError {
     pub message: String,
+    /// The metadata of the response
+    #[serde(skip)]
+    pub meta: Option<Box<ResponseMeta>>,
 }
 
 impl Display for AuthorizationError {
@@ -55,10 +153,59 @@ impl Display for AuthorizationError {
     }
 }
 
+/// An error sent by the api
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiError {
+    pub message: Option<String>,
+    #[serde(default)]
+    pub errors: Vec<ApiErrorDetail>,
+}
+
+impl ApiError {
+    /// Decode an error of the api, a body that isn't an error of the api gives `None`
+    pub(crate) fn from_body(body: &str) -> Option<Self> {
+        serde_json::from_str::<ApiError>(body)
+            .ok()
+            .filter(|error| error.message.is_some() || !error.errors.is_empty())
+    }
+}
+
+impl Display for ApiError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let details = self.errors.iter().map(ToString::to_string);
+        let messages = self.message.clone().into_iter().chain(details);
+
+        f.write_str(&messages.collect::<Vec<_>>().join(", "))
+    }
+}
+
+/// A detail of an [ApiError](crate::ApiError), ie the field that is invalid
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiErrorDetail {
+    pub code: Option<String>,
+    pub message: Option<String>,
+    /// The invalid field of the request
+    pub field: Option<String>,
+}
+
+impl Display for ApiErrorDetail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(code) = &self.code {
+            write!(f, "[{code}] ")?;
+        }
+        if let Some(field) = &self.field {
+            write!(f, "{field}: ")?;
+        }
+        f.write_str(self.message.as_deref().unwrap_or_default())
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::Error;
-    use std::error::Error as StdError;
+    use crate::{ApiError, AuthenticationError, AuthorizationError, Error};
+    use std::{error::Error as StdError, time::Duration};
 
     fn error_trait_implemented<T>()
     where
@@ -70,38 +217,94 @@ mod tests {
     pub fn error_trait() {
         error_trait_implemented::<Error>();
     }
-}
 
-/*
-Note to myself:
+    #[test]
+    fn decode_error_source() {
+        let err = serde_path_to_error::deserialize::<_, u64>(
+            &mut serde_json::Deserializer::from_str("not json"),
+        )
+        .unwrap_err();
 
-// TODO: Add test for PermErr, StatusCode::FORBIDDEN
-By implementing an endpoint and using a mocker (ie https://github.com/lipanski/mockito)
-We need to implement an endpoint first because the token url can't return a 401 or a 403
+        let error = Error::DecodeErr(err);
 
-Example code for AuthenticationError and AuthorizationError:
+        assert!(error.source().is_some());
+    }
 
-StatusCode::UNAUTHORIZED => {
-    let error = response.json::<AuthorizationError>().await.map_err(|err| {
-        error!("Can't decode authentication error");
-        Error::DecodeErr(err)
-    })?;
+    #[test]
+    fn name_not_found_resource() {
+        let error = Error::NotFound {
+            resource: "resource",
+            id: "/v5/organizations/my-asso".to_string(),
+            meta: None,
+        };
 
-    error!("An authentication error as occur, wrong jwt");
+        assert_eq!(
+            error.with_resource("organization", "my-asso").to_string(),
+            "organization my-asso not found"
+        );
+    }
 
-    Err(Error::AuthErr(error))
-}
+    #[test]
+    fn display_inner_message() {
+        let error = Error::PermErr(AuthorizationError {
+            message: "missing FormAdmin role".to_string(),
+            meta: None,
+        });
+        assert!(error.to_string().contains("missing FormAdmin role"));
 
-StatusCode::FORBIDDEN => {
-    let error = response.json::<AuthorizationError>().await.map_err(|err| {
-        error!("Can't decode authentication error");
-        Error::DecodeErr(err)
-    })?;
+        let error = Error::AuthErr(AuthenticationError {
+            error: "unauthorized_client".to_string(),
+            error_description: "Invalid client_id 'abc'".to_string(),
+            meta: None,
+        });
+        assert!(error.to_string().contains("Invalid client_id 'abc'"));
+    }
 
-    error!("Your JWT token hasn't the privileges or Roles for this action");
+    #[test]
+    fn display_retry_after() {
+        let error = Error::RateLimited {
+            retry_after: Some(Duration::from_secs(2)),
+            meta: None,
+        };
+        assert_eq!(error.to_string(), "rate limited by the api, retry after 2s");
 
-    Err(Error::PermErr(error))
-}
+        let error = Error::RateLimited {
+            retry_after: None,
+            meta: None,
+        };
+        assert_eq!(error.to_string(), "rate limited by the api");
+    }
 
+    #[test]
+    fn decode_api_error() {
+        let error = ApiError::from_body(
+            r#"{ "errors": [{ "code": "InvalidField", "message": "must be positive", "field": "totalAmount" }] }"#,
+        )
+        .unwrap();
 
- */
+        assert_eq!(error.errors[0].code.as_deref(), Some("InvalidField"));
+        assert_eq!(
+            error.to_string(),
+            "[InvalidField] totalAmount: must be positive"
+        );
+        assert_eq!(ApiError::from_body("<html>Bad gateway</html>"), None);
+        assert_eq!(ApiError::from_body("{}"), None);
+    }
+
+    #[test]
+    fn wrapped_errors_source() {
+        let error = Error::from(url::Url::parse("not an url").unwrap_err());
+        assert_eq!(error.to_string(), "can't parse url");
+        assert_eq!(
+            error.source().map(ToString::to_string).as_deref(),
+            Some("relative URL without a base")
+        );
+
+        // The message of the api is displayed, not repeated as a source
+        let error = Error::PermErr(AuthorizationError {
+            message: "forbidden".to_string(),
+            meta: None,
+        });
+        assert!(error.source().is_none());
+    }
+}
@@ -0,0 +1,796 @@
+//! `forms` the forms of an organization (events, donations, memberships...)
+
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    client::PAGE_SIZE,
+    decode::{is_strict, unknown_value, Name},
+    pagination::{fetch_all_concurrent, stream_pages},
+    Amount, CustomField, Error, HelloAsso, ItemType, Order, Paginated, PaginationParams, Result,
+};
+
+/// A form as returned by the forms listing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormLightModel {
+    pub form_slug: String,
+    pub form_type: FormType,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub organization_slug: Option<String>,
+    pub banner: Option<Image>,
+    pub logo: Option<Image>,
+    /// Activity of the form, ie "Sport" or "Culture"
+    pub activity_type: Option<String>,
+    pub state: Option<FormState>,
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+/// The public data of a form
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FormPublic {
+    pub form_slug: String,
+    pub form_type: FormType,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub organization_slug: Option<String>,
+    pub organization_name: Option<String>,
+    pub banner: Option<Image>,
+    pub logo: Option<Image>,
+    pub currency: Option<String>,
+    pub activity_type: Option<String>,
+    pub state: Option<FormState>,
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+    /// Start of the sales, when different from the start of the form
+    pub sale_start_date: Option<DateTime<Utc>>,
+    pub sale_end_date: Option<DateTime<Utc>>,
+    /// The prices the payer can choose from
+    #[serde(default)]
+    pub tiers: Vec<Tier>,
+}
+
+impl FormPublic {
+    /// Whether the form accepts contributions at `now`
+    ///
+    /// The form must be public or private (reachable by its link) and `now`
+    /// must be within its start and end dates, when they are set.
+    pub fn is_open(&self, now: DateTime<Utc>) -> bool {
+        let state_open = matches!(self.state, Some(FormState::Public | FormState::Private));
+        let started = self.start_date.is_none_or(|start| start <= now);
+        let not_ended = self.end_date.is_none_or(|end| now <= end);
+
+        state_open && started && not_ended
+    }
+}
+
+/// An image uploaded on helloasso
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Image {
+    pub file_name: String,
+    pub public_url: String,
+}
+
+/// A price of a form, ie a ticket category or a membership fee
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tier {
+    pub id: u64,
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub tier_type: ItemType,
+    /// The price, none when the payer chooses the amount
+    pub price: Option<Amount>,
+    /// Minimum amount when the payer chooses the amount
+    pub min_amount: Option<Amount>,
+    pub max_per_user: Option<u32>,
+    #[serde(default)]
+    pub is_eligible_tax_receipt: bool,
+    pub sale_start_date: Option<DateTime<Utc>>,
+    pub sale_end_date: Option<DateTime<Utc>>,
+}
+
+/// The state of a form
+///
+/// The api adds states, an unknown state is kept as [Other](crate::FormState::Other).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Name", into = "String")]
+pub enum FormState {
+    Public,
+    Private,
+    Draft,
+    Disabled,
+    Closed,
+    Other(String),
+}
+
+impl FormState {
+    /// The name of the state in the api
+    pub fn as_str(&self) -> &str {
+        match self {
+            FormState::Public => "Public",
+            FormState::Private => "Private",
+            FormState::Draft => "Draft",
+            FormState::Disabled => "Disabled",
+            FormState::Closed => "Closed",
+            FormState::Other(state) => state,
+        }
+    }
+}
+
+impl From<String> for FormState {
+    fn from(state: String) -> Self {
+        match state.as_str() {
+            "Public" => FormState::Public,
+            "Private" => FormState::Private,
+            "Draft" => FormState::Draft,
+            "Disabled" => FormState::Disabled,
+            "Closed" => FormState::Closed,
+            _ => FormState::Other(state),
+        }
+    }
+}
+
+impl TryFrom<Name> for FormState {
+    type Error = String;
+
+    fn try_from(name: Name) -> std::result::Result<Self, String> {
+        match FormState::from(name.0) {
+            FormState::Other(state) if is_strict() => Err(unknown_value(&state)),
+            state => Ok(state),
+        }
+    }
+}
+
+impl From<FormState> for String {
+    fn from(state: FormState) -> Self {
+        match state {
+            FormState::Other(state) => state,
+            state => state.as_str().to_string(),
+        }
+    }
+}
+
+/// The kind of a form
+///
+/// The api adds form types, an unknown form type is kept as [Other](crate::FormType::Other).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Name", into = "String")]
+pub enum FormType {
+    Event,
+    Donation,
+    Membership,
+    CrowdFunding,
+    PaymentForm,
+    Checkout,
+    Shop,
+    Other(String),
+}
+
+impl FormType {
+    /// The name of the form type in the api, used in the urls of the forms
+    pub fn as_str(&self) -> &str {
+        match self {
+            FormType::Event => "Event",
+            FormType::Donation => "Donation",
+            FormType::Membership => "Membership",
+            FormType::CrowdFunding => "CrowdFunding",
+            FormType::PaymentForm => "PaymentForm",
+            FormType::Checkout => "Checkout",
+            FormType::Shop => "Shop",
+            FormType::Other(form_type) => form_type,
+        }
+    }
+}
+
+impl From<String> for FormType {
+    fn from(form_type: String) -> Self {
+        match form_type.as_str() {
+            "Event" => FormType::Event,
+            "Donation" => FormType::Donation,
+            "Membership" => FormType::Membership,
+            "CrowdFunding" => FormType::CrowdFunding,
+            "PaymentForm" => FormType::PaymentForm,
+            "Checkout" => FormType::Checkout,
+            "Shop" => FormType::Shop,
+            _ => FormType::Other(form_type),
+        }
+    }
+}
+
+impl TryFrom<Name> for FormType {
+    type Error = String;
+
+    fn try_from(name: Name) -> std::result::Result<Self, String> {
+        match FormType::from(name.0) {
+            FormType::Other(form_type) if is_strict() => Err(unknown_value(&form_type)),
+            form_type => Ok(form_type),
+        }
+    }
+}
+
+impl From<FormType> for String {
+    fn from(form_type: FormType) -> Self {
+        match form_type {
+            FormType::Other(form_type) => form_type,
+            form_type => form_type.as_str().to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for FormType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Filters used when listing the forms of an organization
+///
+/// An empty filter doesn't restrict the listing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FormsFilters {
+    /// States of the forms, ie "Public" or "Draft"
+    pub states: Vec<String>,
+    pub form_types: Vec<FormType>,
+    /// Activity types, kept as strings because helloasso taxonomy evolve
+    pub activity_types: Vec<String>,
+}
+
+impl FormsFilters {
+    fn query(&self) -> Vec<(&str, String)> {
+        let states = self.states.iter().map(|s| ("states", s.clone()));
+        let form_types = self
+            .form_types
+            .iter()
+            .map(|t| ("formTypes", t.as_str().to_string()));
+        let activity_types = self
+            .activity_types
+            .iter()
+            .map(|t| ("activityTypes", t.clone()));
+
+        states.chain(form_types).chain(activity_types).collect()
+    }
+}
+
+/// The body of a quick form creation, see [quick_create_form](crate::HelloAsso::quick_create_form)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickCreateForm {
+    pub title: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner: Option<Image>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_date: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_date: Option<DateTime<Utc>>,
+    /// The prices the payer can choose from
+    #[serde(rename = "tierList", default, skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<QuickCreateTier>,
+}
+
+impl QuickCreateForm {
+    /// Start building a quick form creation
+    ///
+    /// ```rust
+    /// # use helloasso::{Amount, QuickCreateForm};
+    /// let form = QuickCreateForm::builder("Gala 2023")
+    ///     .description("Our yearly gala")
+    ///     .tier("Ticket", Some(Amount::from_euros(25)))
+    ///     .build()
+    ///     .unwrap();
+    /// assert_eq!(form.tiers.len(), 1);
+    /// ```
+    pub fn builder(title: impl Into<String>) -> QuickCreateFormBuilder {
+        QuickCreateFormBuilder {
+            form: QuickCreateForm {
+                title: title.into(),
+                description: None,
+                banner: None,
+                start_date: None,
+                end_date: None,
+                tiers: Vec::new(),
+            },
+        }
+    }
+
+    /// Check the title, the dates and the prices of the form
+    pub fn validate(&self) -> Result<()> {
+        if self.title.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "the title of a form is required".to_string(),
+            ));
+        }
+        if let (Some(start), Some(end)) = (self.start_date, self.end_date) {
+            if end < start {
+                return Err(Error::InvalidInput(format!(
+                    "the form ends ({end}) before it starts ({start})"
+                )));
+            }
+        }
+        if self
+            .tiers
+            .iter()
+            .any(|tier| tier.price == Some(Amount::ZERO))
+        {
+            return Err(Error::InvalidInput(
+                "the price of a tier must be greater than zero".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// A builder for [QuickCreateForm](crate::QuickCreateForm)
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickCreateFormBuilder {
+    form: QuickCreateForm,
+}
+
+impl QuickCreateFormBuilder {
+    pub fn description(&mut self, description: impl Into<String>) -> &mut Self {
+        self.form.description = Some(description.into());
+        self
+    }
+
+    /// Use an image already uploaded on helloasso as banner
+    pub fn banner(&mut self, banner: Image) -> &mut Self {
+        self.form.banner = Some(banner);
+        self
+    }
+
+    /// Open the form between `start` and `end`
+    pub fn dates(&mut self, start: DateTime<Utc>, end: DateTime<Utc>) -> &mut Self {
+        self.form.start_date = Some(start);
+        self.form.end_date = Some(end);
+        self
+    }
+
+    /// Add a price, the payer chooses the amount when `price` is `None`
+    pub fn tier(&mut self, label: impl Into<String>, price: Option<Amount>) -> &mut Self {
+        self.form.tiers.push(QuickCreateTier {
+            label: label.into(),
+            description: None,
+            price,
+        });
+        self
+    }
+
+    /// Build the form, see [validate](crate::QuickCreateForm::validate)
+    pub fn build(&self) -> Result<QuickCreateForm> {
+        self.form.validate()?;
+
+        Ok(self.form.clone())
+    }
+}
+
+/// A price of a [QuickCreateForm](crate::QuickCreateForm)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickCreateTier {
+    pub label: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The price, none when the payer chooses the amount
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub price: Option<Amount>,
+}
+
+/// A form created with [quick_create_form](crate::HelloAsso::quick_create_form)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuickCreatedForm {
+    pub form_slug: String,
+    pub form_type: FormType,
+    /// The public page of the form
+    pub public_url: Option<String>,
+    /// The page of the form in the organization back office
+    pub back_office_url: Option<String>,
+    /// The form embeddable in a website
+    pub widget_full_url: Option<String>,
+}
+
+/// A participant of a form, one per item of the form orders
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Participant {
+    pub order_id: u64,
+    pub item_id: u64,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    /// Email of the payer
+    pub email: Option<String>,
+    /// Answers to the custom questions, in the form order
+    pub custom_fields: Vec<CustomField>,
+}
+
+impl Participant {
+    /// The participants of an order
+    ///
+    /// The identity of the item user is used, the payer identity is used when
+    /// the item has no user.
+    fn from_order(order: Order) -> impl Iterator<Item = Participant> {
+        let payer = order.payer.unwrap_or_default();
+
+        order.items.into_iter().map(move |item| {
+            let (first_name, last_name) = match item.user {
+                Some(user) => (user.first_name, user.last_name),
+                None => (payer.first_name.clone(), payer.last_name.clone()),
+            };
+
+            Participant {
+                order_id: order.id,
+                item_id: item.id,
+                first_name,
+                last_name,
+                email: payer.email.clone(),
+                custom_fields: item.custom_fields,
+            }
+        })
+    }
+}
+
+impl HelloAsso {
+    /// Get the public data of a form, including its tiers
+    pub async fn get_form(
+        &self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+    ) -> Result<FormPublic> {
+        let url = self.endpoint(&[
+            "organizations",
+            organization_slug,
+            "forms",
+            form_type.as_str(),
+            form_slug,
+            "public",
+        ]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("form", form_slug))
+    }
+
+    /// Same as `get_form` but return the json sent by the api, see `get_order_raw`
+    pub async fn get_form_raw(
+        &self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+    ) -> Result<Value> {
+        let url = self.endpoint(&[
+            "organizations",
+            organization_slug,
+            "forms",
+            form_type.as_str(),
+            form_slug,
+            "public",
+        ]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("form", form_slug))
+    }
+
+    /// Get every participant of a form along with their answers to the custom questions
+    ///
+    /// Every page of the form orders is fetched.
+    pub async fn get_form_participants(
+        &self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+    ) -> Result<Vec<Participant>> {
+        let url = self.endpoint(&[
+            "organizations",
+            organization_slug,
+            "forms",
+            form_type.as_str(),
+            form_slug,
+            "orders",
+        ]);
+        let orders = self
+            .get_all_pages::<Order>(url, &[("withDetails", true.to_string())])
+            .await
+            .map_err(|err| err.with_resource("form", form_slug))?;
+
+        Ok(orders
+            .into_iter()
+            .flat_map(Participant::from_order)
+            .collect())
+    }
+
+    /// Create a form with only the essential information, it can be completed in the back office
+    ///
+    /// The form is checked before sending, see [validate](crate::QuickCreateForm::validate).
+    pub async fn quick_create_form(
+        &self,
+        organization_slug: &str,
+        form_type: FormType,
+        form: &QuickCreateForm,
+    ) -> Result<QuickCreatedForm> {
+        form.validate()?;
+
+        let url = self.endpoint(&[
+            "organizations",
+            organization_slug,
+            "forms",
+            form_type.as_str(),
+            "action",
+            "quick-create",
+        ]);
+        self.post(url, &(), form)
+            .await
+            .map_err(|err| err.with_resource("organization", organization_slug))
+    }
+
+    /// List the forms of an organization
+    pub async fn list_forms(
+        &self,
+        organization_slug: &str,
+        filters: &FormsFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<FormLightModel>> {
+        let url = self.endpoint(&["organizations", organization_slug, "forms"]);
+        let mut query = filters.query();
+        query.extend(pagination.query());
+
+        self.get(url, &query)
+            .await
+            .map_err(|err| err.with_resource("organization", organization_slug))
+    }
+
+    /// Stream every form of an organization, the pages are fetched as the stream is consumed
+    pub fn stream_forms<'a>(
+        &'a self,
+        organization_slug: &str,
+        filters: &FormsFilters,
+    ) -> impl Stream<Item = Result<FormLightModel>> + 'a {
+        let organization_slug = organization_slug.to_string();
+        let filters = filters.clone();
+
+        stream_pages(PaginationParams::page(1, PAGE_SIZE), move |params| {
+            let (organization_slug, filters) = (organization_slug.clone(), filters.clone());
+            async move { self.list_forms(&organization_slug, &filters, &params).await }
+        })
+    }
+
+    /// Get every form of an organization, see `fetch_all_organization_orders`
+    pub async fn fetch_all_forms(
+        &self,
+        organization_slug: &str,
+        filters: &FormsFilters,
+        max_in_flight: usize,
+    ) -> Result<Vec<FormLightModel>> {
+        fetch_all_concurrent(PAGE_SIZE, max_in_flight, |params| async move {
+            self.list_forms(organization_slug, filters, &params).await
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        decode::{decode_json, DecodeMode},
+        test_util::MockApi,
+        Amount, CustomField, Error, FormLightModel, FormPublic, FormState, FormType, FormsFilters,
+        ItemType, Order, Participant, QuickCreateForm, QuickCreatedForm,
+    };
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    #[test]
+    fn repeated_activity_types() {
+        let filters = FormsFilters {
+            activity_types: vec!["Sport".to_string(), "Culture".to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            filters.query(),
+            vec![
+                ("activityTypes", "Sport".to_string()),
+                ("activityTypes", "Culture".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn deserialize_activity_type() {
+        let form: FormLightModel = serde_json::from_value(json!({
+            "formSlug": "tournoi",
+            "formType": "Event",
+            "title": "Tournoi",
+            "activityType": "Sport"
+        }))
+        .unwrap();
+
+        assert_eq!(form.activity_type.as_deref(), Some("Sport"));
+    }
+
+    #[test]
+    fn deserialize_form_tiers() {
+        let form: FormPublic = serde_json::from_value(json!({
+            "formSlug": "adhesion-2023",
+            "formType": "Membership",
+            "title": "Adhésion 2023",
+            "banner": { "fileName": "banner.png", "publicUrl": "https://cdn.helloasso.com/banner.png" },
+            "tiers": [
+                { "id": 1, "label": "Adulte", "tierType": "Membership", "price": 3000 },
+                { "id": 2, "label": "Don", "tierType": "Donation", "minAmount": 100, "isEligibleTaxReceipt": true }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            form.banner.map(|banner| banner.public_url),
+            Some("https://cdn.helloasso.com/banner.png".to_string())
+        );
+        assert_eq!(form.tiers.len(), 2);
+        assert_eq!(form.tiers[0].price, Some(Amount::from_cents(3000)));
+        assert_eq!(form.tiers[1].tier_type, ItemType::Donation);
+        assert!(form.tiers[1].is_eligible_tax_receipt);
+    }
+
+    #[test]
+    fn form_is_open() {
+        let form: FormPublic = serde_json::from_value(json!({
+            "formSlug": "gala",
+            "formType": "Event",
+            "state": "Public",
+            "startDate": "2023-06-01T00:00:00+02:00",
+            "endDate": "2023-06-30T23:59:59+02:00"
+        }))
+        .unwrap();
+
+        assert!(form.is_open(Utc.with_ymd_and_hms(2023, 6, 15, 12, 0, 0).unwrap()));
+        assert!(!form.is_open(Utc.with_ymd_and_hms(2023, 7, 1, 12, 0, 0).unwrap()));
+        assert!(!form.is_open(Utc.with_ymd_and_hms(2023, 5, 1, 12, 0, 0).unwrap()));
+
+        let draft = FormPublic {
+            state: Some(FormState::Draft),
+            ..form
+        };
+        assert!(!draft.is_open(Utc.with_ymd_and_hms(2023, 6, 15, 12, 0, 0).unwrap()));
+    }
+
+    #[test]
+    fn participants_from_order() {
+        let order: Order = serde_json::from_value(json!({
+            "id": 12,
+            "payer": { "email": "jane@example.com", "firstName": "Jane", "lastName": "Doe" },
+            "items": [
+                {
+                    "id": 1,
+                    "amount": 1000,
+                    "type": "Registration",
+                    "user": { "firstName": "John", "lastName": "Doe" },
+                    "customFields": [
+                        { "name": "Size", "answer": "M" },
+                        { "name": "Diet", "answer": "Vegan" }
+                    ]
+                },
+                { "id": 2, "amount": 1000, "type": "Registration" }
+            ]
+        }))
+        .unwrap();
+
+        let participants = Participant::from_order(order).collect::<Vec<_>>();
+
+        assert_eq!(participants.len(), 2);
+        assert_eq!(participants[0].first_name.as_deref(), Some("John"));
+        assert_eq!(participants[0].email.as_deref(), Some("jane@example.com"));
+        assert_eq!(
+            participants[0].custom_fields,
+            vec![
+                CustomField {
+                    id: None,
+                    name: "Size".to_string(),
+                    field_type: None,
+                    answer: "M".to_string()
+                },
+                CustomField {
+                    id: None,
+                    name: "Diet".to_string(),
+                    field_type: None,
+                    answer: "Vegan".to_string()
+                }
+            ]
+        );
+        assert_eq!(participants[1].first_name.as_deref(), Some("Jane"));
+    }
+
+    #[test]
+    fn quick_create_form() {
+        let form = QuickCreateForm::builder("Gala")
+            .tier("Ticket", Some(Amount::from_euros(25)))
+            .tier("Free price", None)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            serde_json::to_value(&form).unwrap(),
+            json!({
+                "title": "Gala",
+                "tierList": [{ "label": "Ticket", "price": 2500 }, { "label": "Free price" }]
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_quick_create_form() {
+        let start = Utc.with_ymd_and_hms(2023, 6, 1, 0, 0, 0).unwrap();
+        let end = Utc.with_ymd_and_hms(2023, 5, 1, 0, 0, 0).unwrap();
+
+        assert!(matches!(
+            QuickCreateForm::builder(" ").build(),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            QuickCreateForm::builder("Gala").dates(start, end).build(),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            QuickCreateForm::builder("Gala")
+                .tier("Ticket", Some(Amount::ZERO))
+                .build(),
+            Err(Error::InvalidInput(_))
+        ));
+    }
+
+    #[test]
+    fn deserialize_quick_created_form() {
+        let form: QuickCreatedForm = serde_json::from_value(json!({
+            "formSlug": "gala",
+            "formType": "Event",
+            "publicUrl": "https://www.helloasso.com/associations/my-asso/evenements/gala",
+            "backOfficeUrl": "https://admin.helloasso.com/my-asso/evenements/gala"
+        }))
+        .unwrap();
+
+        assert_eq!(form.form_slug, "gala");
+        assert!(form.back_office_url.is_some());
+    }
+
+    #[test]
+    fn form_type_names() {
+        let form_type: FormType = serde_json::from_str("\"CrowdFunding\"").unwrap();
+        assert_eq!(form_type.as_str(), "CrowdFunding");
+
+        let form_type: FormType = serde_json::from_str("\"NewFormType\"").unwrap();
+        assert_eq!(form_type, FormType::Other("NewFormType".to_string()));
+        assert_eq!(form_type.as_str(), "NewFormType");
+        assert_eq!(
+            serde_json::to_string(&form_type).unwrap(),
+            "\"NewFormType\""
+        );
+        assert!(decode_json::<FormType>(b"\"NewFormType\"", DecodeMode::Strict).is_err());
+    }
+
+    #[tokio::test]
+    async fn quick_create_form_unknown_organization() {
+        let api = MockApi::start().await;
+        let client = api.client().await.unwrap();
+        let form = QuickCreateForm::builder("Gala 2023")
+            .tier("Ticket", Some(Amount::from_euros(25)))
+            .build()
+            .unwrap();
+
+        let created = client
+            .quick_create_form("unknown-asso", FormType::Event, &form)
+            .await;
+        assert!(
+            matches!(&created, Err(Error::NotFound { resource: "organization", id, .. }) if id == "unknown-asso"),
+            "{created:?}"
+        );
+    }
+}
@@ -0,0 +1,126 @@
+//! `interceptor` hooks called on every request sent to the api
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+};
+
+use reqwest::{Request, Response};
+
+/// A hook called on every request to the api and every response, set on the builder with `interceptor`
+///
+/// It can add headers, log or measure the requests. It is called on each attempt when
+/// a request is retried, after the authorization header is set. The requests fetching
+/// the tokens aren't intercepted. A closure taking the request is an interceptor.
+///
+/// ```rust
+/// # use helloasso::HelloAsso;
+/// let builder = HelloAsso::builder("client_id".to_string(), "client_secret".to_string())
+///     .with_interceptor(|request: &mut reqwest::Request| {
+///         request
+///             .headers_mut()
+///             .insert("x-correlation-id", "3f2a".parse().unwrap());
+///     });
+/// ```
+pub trait Interceptor: Send + Sync {
+    /// Called before the request is sent
+    fn on_request(&self, _request: &mut Request) {}
+
+    /// Called with the response, before it is decoded
+    fn on_response(&self, _response: &Response) {}
+}
+
+impl<F: Fn(&mut Request) + Send + Sync> Interceptor for F {
+    fn on_request(&self, request: &mut Request) {
+        self(request)
+    }
+}
+
+/// The interceptors of a client, called in the order they were added
+#[derive(Clone, Default)]
+pub(crate) struct Interceptors(Vec<Arc<dyn Interceptor>>);
+
+impl Interceptors {
+    pub(crate) fn push(&mut self, interceptor: impl Interceptor + 'static) {
+        self.0.push(Arc::new(interceptor));
+    }
+
+    pub(crate) fn on_request(&self, request: &mut Request) {
+        self.0
+            .iter()
+            .for_each(|interceptor| interceptor.on_request(request));
+    }
+
+    pub(crate) fn on_response(&self, response: &Response) {
+        self.0
+            .iter()
+            .for_each(|interceptor| interceptor.on_response(response));
+    }
+}
+
+impl Debug for Interceptors {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Interceptors({})", self.0.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use reqwest::{Request, Response};
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, ResponseTemplate,
+    };
+
+    use super::Interceptor;
+    use crate::{
+        test_util::{self, MockApi, CLIENT_ID, CLIENT_SECRET, PAYMENT_ID},
+        HelloAsso,
+    };
+
+    /// Record the status of the responses
+    #[derive(Default)]
+    struct Statuses(Mutex<Vec<u16>>);
+
+    impl Interceptor for Arc<Statuses> {
+        fn on_response(&self, response: &Response) {
+            self.0.lock().unwrap().push(response.status().as_u16());
+        }
+    }
+
+    #[tokio::test]
+    async fn intercept_requests() {
+        let api = MockApi::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v5/payments/{PAYMENT_ID}")))
+            .and(header("x-audit", "on"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(test_util::payment()))
+            .with_priority(1)
+            .expect(1)
+            .mount(api.server())
+            .await;
+        let statuses = Arc::new(Statuses::default());
+        let client = HelloAsso::builder(CLIENT_ID.to_string(), CLIENT_SECRET.to_string())
+            .with_base_url(&api.uri())
+            .unwrap()
+            .with_interceptor(|request: &mut Request| {
+                request
+                    .headers_mut()
+                    .insert("x-audit", "on".parse().unwrap());
+            })
+            .with_interceptor(statuses.clone())
+            .get_token()
+            .await
+            .unwrap()
+            .config_client()
+            .unwrap()
+            .build();
+
+        client.get_payment(PAYMENT_ID).await.unwrap();
+        assert!(client.get_payment(1).await.is_err());
+
+        assert_eq!(*statuses.0.lock().unwrap(), [200, 404]);
+    }
+}
@@ -0,0 +1,339 @@
+//! `items` the items bought in the orders, ie a ticket or a membership
+
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    client::PAGE_SIZE,
+    decode::{is_strict, unknown_value, Name},
+    pagination::{fetch_all_concurrent, stream_pages},
+    Amount, CustomField, FormType, HelloAsso, ItemType, OrderItemOption, OrderPayment, Paginated,
+    PaginationParams, Payer, PaymentOrder, Result, SortOrder, User,
+};
+
+/// An item bought in an order, with the order it belongs to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Item {
+    pub id: u64,
+    pub name: Option<String>,
+    pub amount: Amount,
+    /// The tier of the form this item was bought from
+    pub tier_id: Option<u64>,
+    #[serde(rename = "type")]
+    pub item_type: ItemType,
+    pub state: Option<ItemState>,
+    pub order: Option<PaymentOrder>,
+    pub payer: Option<Payer>,
+    /// The person this item is for, ie the participant of an event
+    pub user: Option<User>,
+    /// Answers to the custom questions of the form, in the form order
+    #[serde(default)]
+    pub custom_fields: Vec<CustomField>,
+    /// The extra options chosen with this item, only sent with the details
+    #[serde(default)]
+    pub options: Vec<OrderItemOption>,
+    /// The payments of this item, only sent with the details
+    #[serde(default)]
+    pub payments: Vec<OrderPayment>,
+}
+
+/// The state of an [Item](crate::Item)
+///
+/// The api adds states, an unknown state is kept as [Other](crate::ItemState::Other).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Name", into = "String")]
+pub enum ItemState {
+    /// The item is waiting for its payment
+    Waiting,
+    Processed,
+    Registered,
+    Deleted,
+    Refunded,
+    Canceled,
+    Contested,
+    Other(String),
+}
+
+impl ItemState {
+    /// The name of the state in the api, used by the filters
+    pub fn as_str(&self) -> &str {
+        match self {
+            ItemState::Waiting => "Waiting",
+            ItemState::Processed => "Processed",
+            ItemState::Registered => "Registered",
+            ItemState::Deleted => "Deleted",
+            ItemState::Refunded => "Refunded",
+            ItemState::Canceled => "Canceled",
+            ItemState::Contested => "Contested",
+            ItemState::Other(state) => state,
+        }
+    }
+}
+
+impl From<String> for ItemState {
+    fn from(state: String) -> Self {
+        match state.as_str() {
+            "Waiting" => ItemState::Waiting,
+            "Processed" => ItemState::Processed,
+            "Registered" => ItemState::Registered,
+            "Deleted" => ItemState::Deleted,
+            "Refunded" => ItemState::Refunded,
+            "Canceled" => ItemState::Canceled,
+            "Contested" => ItemState::Contested,
+            _ => ItemState::Other(state),
+        }
+    }
+}
+
+impl TryFrom<Name> for ItemState {
+    type Error = String;
+
+    fn try_from(name: Name) -> std::result::Result<Self, String> {
+        match ItemState::from(name.0) {
+            ItemState::Other(state) if is_strict() => Err(unknown_value(&state)),
+            state => Ok(state),
+        }
+    }
+}
+
+impl From<ItemState> for String {
+    fn from(state: ItemState) -> Self {
+        match state {
+            ItemState::Other(state) => state,
+            state => state.as_str().to_string(),
+        }
+    }
+}
+
+/// Filters used when listing items
+///
+/// An empty filter doesn't restrict the listing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ItemFilters {
+    /// Types of the tiers the items were bought from
+    pub tier_types: Vec<ItemType>,
+    pub item_states: Vec<ItemState>,
+    /// Name of the tier the items were bought from
+    pub tier_name: Option<String>,
+    /// Order of the items by date
+    pub sort_order: Option<SortOrder>,
+}
+
+impl ItemFilters {
+    fn query(&self) -> Vec<(&str, String)> {
+        let tier_types = self
+            .tier_types
+            .iter()
+            .map(|t| ("tierTypes", t.as_str().to_string()));
+        let item_states = self
+            .item_states
+            .iter()
+            .map(|s| ("itemStates", s.as_str().to_string()));
+
+        let mut query: Vec<_> = tier_types.chain(item_states).collect();
+        if let Some(tier_name) = &self.tier_name {
+            query.push(("tierName", tier_name.clone()));
+        }
+        if let Some(sort_order) = self.sort_order {
+            query.push(("sortOrder", sort_order.as_str().to_string()));
+        }
+        query
+    }
+}
+
+impl HelloAsso {
+    /// Get an item, its options and payments are only sent when `with_details` is set
+    pub async fn get_item(&self, item_id: u64, with_details: bool) -> Result<Item> {
+        let url = self.endpoint(&["items", &item_id.to_string()]);
+        self.get(url, &[("withDetails", with_details)])
+            .await
+            .map_err(|err| err.with_resource("item", item_id))
+    }
+
+    /// Same as `get_item` but return the json sent by the api, see `get_order_raw`
+    pub async fn get_item_raw(&self, item_id: u64, with_details: bool) -> Result<Value> {
+        let url = self.endpoint(&["items", &item_id.to_string()]);
+        self.get(url, &[("withDetails", with_details)])
+            .await
+            .map_err(|err| err.with_resource("item", item_id))
+    }
+
+    /// List the items bought in the forms of an organization
+    pub async fn list_organization_items(
+        &self,
+        organization_slug: &str,
+        filters: &ItemFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Item>> {
+        let url = self.endpoint(&["organizations", organization_slug, "items"]);
+        let mut query = filters.query();
+        query.extend(pagination.query());
+
+        self.get(url, &query)
+            .await
+            .map_err(|err| err.with_resource("organization", organization_slug))
+    }
+
+    /// Stream every item of an organization, the pages are fetched as the stream is consumed
+    pub fn stream_organization_items<'a>(
+        &'a self,
+        organization_slug: &str,
+        filters: &ItemFilters,
+    ) -> impl Stream<Item = Result<Item>> + 'a {
+        let organization_slug = organization_slug.to_string();
+        let filters = filters.clone();
+
+        stream_pages(PaginationParams::page(1, PAGE_SIZE), move |params| {
+            let (organization_slug, filters) = (organization_slug.clone(), filters.clone());
+            async move {
+                self.list_organization_items(&organization_slug, &filters, &params)
+                    .await
+            }
+        })
+    }
+
+    /// Get every item of an organization, see `fetch_all_organization_orders`
+    pub async fn fetch_all_organization_items(
+        &self,
+        organization_slug: &str,
+        filters: &ItemFilters,
+        max_in_flight: usize,
+    ) -> Result<Vec<Item>> {
+        fetch_all_concurrent(PAGE_SIZE, max_in_flight, |params| async move {
+            self.list_organization_items(organization_slug, filters, &params)
+                .await
+        })
+        .await
+    }
+
+    /// List the items bought in a form, ie the attendees of an event
+    pub async fn list_form_items(
+        &self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+        filters: &ItemFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Item>> {
+        let url = self.endpoint(&[
+            "organizations",
+            organization_slug,
+            "forms",
+            form_type.as_str(),
+            form_slug,
+            "items",
+        ]);
+        let mut query = filters.query();
+        query.extend(pagination.query());
+
+        self.get(url, &query)
+            .await
+            .map_err(|err| err.with_resource("form", form_slug))
+    }
+
+    /// Stream every item of a form, see `stream_organization_items`
+    pub fn stream_form_items<'a>(
+        &'a self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+        filters: &ItemFilters,
+    ) -> impl Stream<Item = Result<Item>> + 'a {
+        let form = [organization_slug, form_slug].map(str::to_string);
+        let filters = filters.clone();
+
+        stream_pages(PaginationParams::page(1, PAGE_SIZE), move |params| {
+            let ([organization_slug, form_slug], filters) = (form.clone(), filters.clone());
+            let form_type = form_type.clone();
+            async move {
+                self.list_form_items(&organization_slug, form_type, &form_slug, &filters, &params)
+                    .await
+            }
+        })
+    }
+
+    /// Get every item of a form, see `fetch_all_organization_orders`
+    pub async fn fetch_all_form_items(
+        &self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+        filters: &ItemFilters,
+        max_in_flight: usize,
+    ) -> Result<Vec<Item>> {
+        fetch_all_concurrent(PAGE_SIZE, max_in_flight, |params| {
+            let form_type = form_type.clone();
+            async move {
+                self.list_form_items(organization_slug, form_type, form_slug, filters, &params)
+                    .await
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Amount, Item, ItemFilters, ItemState, ItemType, SortOrder};
+    use serde_json::json;
+
+    #[test]
+    fn deserialize_item() {
+        let item: Item = serde_json::from_value(json!({
+            "id": 1,
+            "name": "Ticket",
+            "amount": 2500,
+            "type": "Registration",
+            "state": "Processed",
+            "order": { "id": 12, "formSlug": "gala", "formType": "Event" },
+            "payer": { "email": "jane@example.com" },
+            "user": { "firstName": "John", "lastName": "Doe" },
+            "customFields": [{ "name": "Size", "answer": "M" }]
+        }))
+        .unwrap();
+
+        assert_eq!(item.amount, Amount::from_cents(2500));
+        assert_eq!(item.state, Some(ItemState::Processed));
+        assert_eq!(item.order.map(|order| order.id), Some(12));
+        assert_eq!(item.custom_fields[0].answer, "M");
+    }
+
+    #[test]
+    fn deserialize_item_details() {
+        let item: Item = serde_json::from_value(json!({
+            "id": 1,
+            "amount": 3000,
+            "type": "Membership",
+            "customFields": [{ "name": "Licence", "answer": "123" }],
+            "options": [{ "name": "T-shirt", "amount": 1000, "customFields": [{ "name": "Size", "answer": "M" }] }],
+            "payments": [{ "id": 42, "amount": 4000, "state": "Authorized" }]
+        }))
+        .unwrap();
+
+        assert_eq!(item.options[0].custom_fields[0].answer, "M");
+        assert_eq!(item.payments[0].id, 42);
+    }
+
+    #[test]
+    fn item_filters_query() {
+        assert!(ItemFilters::default().query().is_empty());
+
+        let filters = ItemFilters {
+            tier_types: vec![ItemType::Registration, ItemType::Membership],
+            item_states: vec![ItemState::Processed],
+            sort_order: Some(SortOrder::Desc),
+            ..Default::default()
+        };
+        assert_eq!(
+            filters.query(),
+            vec![
+                ("tierTypes", "Registration".to_string()),
+                ("tierTypes", "Membership".to_string()),
+                ("itemStates", "Processed".to_string()),
+                ("sortOrder", "Desc".to_string()),
+            ]
+        );
+    }
+}
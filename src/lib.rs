@@ -2,8 +2,75 @@
 //!
 //! `helloasso` is a create used to interact with the [helloasso api](https://api.helloasso.com/v5/swagger/ui/index#/).
 //! It is not affiliated to helloasso.
+mod amount;
+mod authorization;
+#[cfg(feature = "blocking")]
+pub mod blocking;
+mod checkout;
 mod client;
+mod decode;
+mod directory;
 mod error;
+mod forms;
+mod interceptor;
+mod items;
+mod logging;
+mod meta;
+mod orders;
+mod organizations;
+mod pagination;
+mod partners;
+mod payments;
+mod rate_limit;
+mod retry;
+mod runtime;
+mod scoped;
+mod tags;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+mod token;
+mod transport;
+mod users;
+pub mod values;
+pub mod webhooks;
 
-pub use crate::client::HelloAsso;
-pub use crate::error::{AuthenticationError, AuthorizationError, Error};
+pub use crate::amount::Amount;
+pub use crate::authorization::{OrganizationToken, Pkce};
+pub use crate::checkout::{
+    CheckoutIntent, CheckoutIntentBuilder, CheckoutIntentRequest, CheckoutPayer, CheckoutTerm,
+    Metadata, METADATA_MAX_SIZE,
+};
+pub use crate::client::{Environment, HelloAsso};
+pub use crate::decode::DecodeMode;
+pub use crate::directory::{DirectoryFormsQuery, DirectoryOrgQuery, OrgSort};
+pub use crate::error::{
+    ApiError, ApiErrorDetail, AuthenticationError, AuthorizationError, DecodeError, Error, Result,
+};
+pub use crate::forms::{
+    FormLightModel, FormPublic, FormState, FormType, FormsFilters, Image, Participant,
+    QuickCreateForm, QuickCreateFormBuilder, QuickCreateTier, QuickCreatedForm, Tier,
+};
+pub use crate::interceptor::Interceptor;
+pub use crate::items::{Item, ItemFilters, ItemState};
+pub use crate::meta::ResponseMeta;
+pub use crate::orders::{
+    CustomField, ItemType, Order, OrderAmount, OrderCancellation, OrderDetail, OrderFilters,
+    OrderItem, OrderItemOption, OrderPayment, Payer, SortOrder, User,
+};
+pub use crate::organizations::{
+    Address, GeoLocation, Organization, OrganizationCategory, OrganizationLegalInfo,
+    OrganizationProfile, Siret, SocialLink,
+};
+pub use crate::pagination::{Paginated, Pagination, PaginationParams};
+pub use crate::partners::{NotificationType, NotificationUrl, Partner};
+pub use crate::payments::{
+    CashOutState, Payment, PaymentFilters, PaymentMeans, PaymentOrder, PaymentState,
+    RefundOperation, RefundOptions,
+};
+pub use crate::rate_limit::RateLimit;
+pub use crate::retry::{RetryEvent, RetryPolicy};
+pub use crate::scoped::{FormClient, OrganizationClient};
+pub use crate::tags::TagStatistics;
+pub use crate::token::{Session, StoredToken, TokenValue};
+pub use crate::transport::HttpTransport;
+pub use crate::users::{AuthorizedOrg, OrganizationRole};
@@ -0,0 +1,94 @@
+//! `logging` the logging macros used across the crate
+//!
+//! This is the only place where the `log` and `tracing` features are checked. When
+//! both features are disabled the arguments are still type checked but nothing is emitted.
+
+macro_rules! log_error {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        ::log::error!($($arg)+);
+        #[cfg(feature = "tracing")]
+        ::tracing::error!($($arg)+);
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        let _ = format_args!($($arg)+);
+    }};
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        ::log::warn!($($arg)+);
+        #[cfg(feature = "tracing")]
+        ::tracing::warn!($($arg)+);
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        let _ = format_args!($($arg)+);
+    }};
+}
+
+macro_rules! log_info {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        ::log::info!($($arg)+);
+        #[cfg(feature = "tracing")]
+        ::tracing::info!($($arg)+);
+        #[cfg(not(any(feature = "log", feature = "tracing")))]
+        let _ = format_args!($($arg)+);
+    }};
+}
+
+pub(crate) use {log_error, log_info, log_warn};
+
+use std::time::Duration;
+
+use reqwest::StatusCode;
+
+/// The span of a request to the api
+///
+/// The status and latency are recorded once the response is received, see `record_response`.
+#[cfg(feature = "tracing")]
+pub(crate) fn request_span(request: &reqwest::Request) -> tracing::Span {
+    let endpoint = request.url().path();
+
+    tracing::info_span!(
+        "helloasso_request",
+        method = %request.method(),
+        endpoint,
+        organization = organization_slug(endpoint).unwrap_or_default(),
+        status = tracing::field::Empty,
+        latency_ms = tracing::field::Empty,
+    )
+}
+
+/// Record the status and latency of a request on its span
+pub(crate) fn record_response(status: StatusCode, latency: Duration) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::Span::current();
+        span.record("status", status.as_u16());
+        span.record("latency_ms", latency.as_millis() as u64);
+    }
+    #[cfg(not(feature = "tracing"))]
+    let _ = (status, latency);
+}
+
+/// The organization slug of an endpoint path, ie `/v5/organizations/{slug}/forms`
+#[cfg(feature = "tracing")]
+fn organization_slug(path: &str) -> Option<&str> {
+    let mut segments = path.split('/');
+    segments.find(|segment| *segment == "organizations")?;
+    segments.next().filter(|slug| !slug.is_empty())
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::organization_slug;
+
+    #[test]
+    fn endpoint_organization() {
+        assert_eq!(
+            organization_slug("/v5/organizations/my-asso/forms"),
+            Some("my-asso")
+        );
+        assert_eq!(organization_slug("/v5/payments/42"), None);
+    }
+}
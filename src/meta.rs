@@ -0,0 +1,77 @@
+//! `meta` the metadata of the api responses
+
+use reqwest::{
+    header::{HeaderMap, HeaderName},
+    StatusCode,
+};
+
+/// The metadata of a response of the api, see the `*_with_meta` endpoints
+/// and [response_meta](crate::Error::response_meta) for the errors
+///
+/// The request id identifies the request for the helloasso support, the rate limit
+/// counters tell how many requests can still be sent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResponseMeta {
+    pub status: StatusCode,
+    /// `X-Request-Id`
+    pub request_id: Option<String>,
+    /// `X-RateLimit-Limit`, the number of requests allowed in the current window
+    pub rate_limit: Option<u64>,
+    /// `X-RateLimit-Remaining`
+    pub rate_limit_remaining: Option<u64>,
+    /// `X-RateLimit-Reset`, as sent by the api
+    pub rate_limit_reset: Option<String>,
+    /// Every header of the response
+    pub headers: HeaderMap,
+}
+
+impl ResponseMeta {
+    pub(crate) fn new(status: StatusCode, headers: &HeaderMap) -> Self {
+        ResponseMeta {
+            status,
+            request_id: header_str(headers, "x-request-id"),
+            rate_limit: header_str(headers, "x-ratelimit-limit").and_then(|v| v.parse().ok()),
+            rate_limit_remaining: header_str(headers, "x-ratelimit-remaining")
+                .and_then(|v| v.parse().ok()),
+            rate_limit_reset: header_str(headers, "x-ratelimit-reset"),
+            headers: headers.clone(),
+        }
+    }
+}
+
+fn header_str(headers: &HeaderMap, name: &'static str) -> Option<String> {
+    headers
+        .get(HeaderName::from_static(name))?
+        .to_str()
+        .ok()
+        .map(|value| value.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{
+        header::{HeaderMap, HeaderValue},
+        StatusCode,
+    };
+
+    use crate::ResponseMeta;
+
+    #[test]
+    fn read_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", HeaderValue::from_static("0HMV8"));
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static("100"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("97"));
+        headers.insert(
+            "x-ratelimit-reset",
+            HeaderValue::from_static("not a number"),
+        );
+
+        let meta = ResponseMeta::new(StatusCode::OK, &headers);
+
+        assert_eq!(meta.request_id.as_deref(), Some("0HMV8"));
+        assert_eq!(meta.rate_limit, Some(100));
+        assert_eq!(meta.rate_limit_remaining, Some(97));
+        assert_eq!(meta.rate_limit_reset.as_deref(), Some("not a number"));
+    }
+}
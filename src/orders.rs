@@ -0,0 +1,715 @@
+//! `orders` the orders made on an organization forms
+
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    client::PAGE_SIZE,
+    decode::{is_strict, unknown_value, Name},
+    pagination::{fetch_all_concurrent, stream_pages},
+    Amount, FormType, HelloAsso, Paginated, PaginationParams, PaymentState, ResponseMeta, Result,
+};
+
+/// An order, a payer can buy several items in one order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Order {
+    pub id: u64,
+    pub date: Option<DateTime<Utc>>,
+    pub form_slug: Option<String>,
+    pub form_type: Option<FormType>,
+    pub organization_slug: Option<String>,
+    pub amount: Option<OrderAmount>,
+    pub payer: Option<Payer>,
+    #[serde(default)]
+    pub items: Vec<OrderItem>,
+    #[serde(default)]
+    pub payments: Vec<OrderPayment>,
+}
+
+/// An order with its items and payments, as returned by [get_order](crate::HelloAsso::get_order)
+pub type OrderDetail = Order;
+
+/// The amounts of an [Order](crate::Order)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderAmount {
+    pub total: Amount,
+    #[serde(default)]
+    pub vat: Amount,
+    #[serde(default)]
+    pub discount: Amount,
+}
+
+/// A summary of a payment of an [Order](crate::Order), see [Payment](crate::Payment) for the details
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderPayment {
+    pub id: u64,
+    pub amount: Amount,
+    pub state: Option<PaymentState>,
+    pub date: Option<DateTime<Utc>>,
+}
+
+/// The result of [cancel_order](crate::HelloAsso::cancel_order)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderCancellation {
+    /// The future payments of the order that won't be collected
+    #[serde(default)]
+    pub payments: Vec<OrderPayment>,
+}
+
+impl Order {
+    /// Amount of the order that can be declared on a fiscal receipt
+    ///
+    /// Only the items that are a donation and whose tier is marked as eligible
+    /// to tax receipt count. The organization must also be allowed to issue
+    /// fiscal receipts, this is not checked here.
+    pub fn eligible_receipt_amount(&self) -> Amount {
+        self.items
+            .iter()
+            .filter(|item| item.item_type.is_donation() && item.is_eligible_tax_receipt)
+            .map(|item| item.amount)
+            .sum()
+    }
+}
+
+/// Filters used when listing orders
+///
+/// Unset filters are not sent, the api default are used.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OrderFilters {
+    /// Only the orders made after this date
+    pub from: Option<DateTime<Utc>>,
+    /// Only the orders made before this date
+    pub to: Option<DateTime<Utc>>,
+    /// Only the orders containing items of these types, an [Other](crate::ItemType::Other) type is sent by its name
+    pub item_types: Vec<ItemType>,
+    /// Include the items and payments of the orders
+    pub with_details: bool,
+    /// Include the total number of orders in the pagination
+    pub with_count: bool,
+    /// Order of the orders by date
+    pub sort_order: Option<SortOrder>,
+}
+
+impl OrderFilters {
+    pub fn with_from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    pub fn with_to(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    pub fn with_item_type(mut self, item_type: ItemType) -> Self {
+        self.item_types.push(item_type);
+        self
+    }
+
+    pub fn with_details(mut self) -> Self {
+        self.with_details = true;
+        self
+    }
+
+    pub fn with_count(mut self) -> Self {
+        self.with_count = true;
+        self
+    }
+
+    pub fn with_sort_order(mut self, sort_order: SortOrder) -> Self {
+        self.sort_order = Some(sort_order);
+        self
+    }
+
+    fn query(&self) -> Vec<(&str, String)> {
+        let mut query: Vec<_> = self
+            .item_types
+            .iter()
+            .map(|item_type| ("tierTypes", item_type.as_str().to_string()))
+            .collect();
+        if let Some(from) = self.from {
+            query.push(("from", from.to_rfc3339()));
+        }
+        if let Some(to) = self.to {
+            query.push(("to", to.to_rfc3339()));
+        }
+        if self.with_details {
+            query.push(("withDetails", true.to_string()));
+        }
+        if self.with_count {
+            query.push(("withCount", true.to_string()));
+        }
+        if let Some(sort_order) = self.sort_order {
+            query.push(("sortOrder", sort_order.as_str().to_string()));
+        }
+        query
+    }
+}
+
+/// Order of a listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SortOrder {
+    /// Oldest first
+    Asc,
+    /// Newest first
+    Desc,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Asc => "Asc",
+            SortOrder::Desc => "Desc",
+        }
+    }
+}
+
+impl HelloAsso {
+    /// Get an order
+    pub async fn get_order(&self, order_id: u64) -> Result<Order> {
+        let url = self.endpoint(&["orders", &order_id.to_string()]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("order", order_id))
+    }
+
+    /// Same as `get_order`, along with the request id and rate limit of the response
+    pub async fn get_order_with_meta(&self, order_id: u64) -> Result<(Order, ResponseMeta)> {
+        let url = self.endpoint(&["orders", &order_id.to_string()]);
+        self.get_with_meta(url, &())
+            .await
+            .map_err(|err| err.with_resource("order", order_id))
+    }
+
+    /// Same as `get_order` but return the json sent by the api, with the fields the models don't cover
+    pub async fn get_order_raw(&self, order_id: u64) -> Result<Value> {
+        let url = self.endpoint(&["orders", &order_id.to_string()]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("order", order_id))
+    }
+
+    /// Cancel an order, its future payments, ie the next installments, won't be collected
+    ///
+    /// The payments already made are not refunded, see [refund_payment](crate::HelloAsso::refund_payment).
+    pub async fn cancel_order(&self, order_id: u64) -> Result<OrderCancellation> {
+        // The api expects an empty object as the body
+        let url = self.endpoint(&["orders", &order_id.to_string(), "cancel"]);
+        let cancellation: Option<OrderCancellation> = self
+            .post(url, &(), &serde_json::Map::new())
+            .await
+            .map_err(|err| err.with_resource("order", order_id))?;
+
+        // The api may answer without a body
+        Ok(cancellation.unwrap_or_default())
+    }
+
+    /// List the orders of an organization
+    pub async fn list_organization_orders(
+        &self,
+        organization_slug: &str,
+        filters: &OrderFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Order>> {
+        let url = self.endpoint(&["organizations", organization_slug, "orders"]);
+        let mut query = filters.query();
+        query.extend(pagination.query());
+
+        self.get(url, &query)
+            .await
+            .map_err(|err| err.with_resource("organization", organization_slug))
+    }
+
+    /// Stream every order of an organization, the pages are fetched as the stream is consumed
+    ///
+    /// ```rust,no_run
+    /// # use helloasso::{HelloAsso, OrderFilters};
+    /// use futures_util::StreamExt;
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> helloasso::Result<()> {
+    /// # let client = HelloAsso::new("client_id".to_string(), "client_secret".to_string()).await?;
+    ///
+    /// let orders = client.stream_organization_orders("my-asso", &OrderFilters::default());
+    /// futures_util::pin_mut!(orders);
+    /// while let Some(order) = orders.next().await {
+    ///     println!("{}", order?.id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn stream_organization_orders<'a>(
+        &'a self,
+        organization_slug: &str,
+        filters: &OrderFilters,
+    ) -> impl Stream<Item = Result<Order>> + 'a {
+        let organization_slug = organization_slug.to_string();
+        let filters = filters.clone();
+
+        stream_pages(PaginationParams::page(1, PAGE_SIZE), move |params| {
+            let (organization_slug, filters) = (organization_slug.clone(), filters.clone());
+            async move {
+                self.list_organization_orders(&organization_slug, &filters, &params)
+                    .await
+            }
+        })
+    }
+
+    /// Get every order of an organization, fetching up to `max_in_flight` pages at once
+    ///
+    /// Faster than `stream_organization_orders` for a full export, the orders are in the
+    /// same order but are all kept in memory. The pages are requested by index, an order
+    /// created or deleted during the export shifts the next pages, so an order can be
+    /// missed or returned twice. Filter on a closed date range for an exact export.
+    pub async fn fetch_all_organization_orders(
+        &self,
+        organization_slug: &str,
+        filters: &OrderFilters,
+        max_in_flight: usize,
+    ) -> Result<Vec<Order>> {
+        fetch_all_concurrent(PAGE_SIZE, max_in_flight, |params| async move {
+            self.list_organization_orders(organization_slug, filters, &params)
+                .await
+        })
+        .await
+    }
+
+    /// List the orders of a form
+    pub async fn list_form_orders(
+        &self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+        filters: &OrderFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Order>> {
+        let url = self.endpoint(&[
+            "organizations",
+            organization_slug,
+            "forms",
+            form_type.as_str(),
+            form_slug,
+            "orders",
+        ]);
+        let mut query = filters.query();
+        query.extend(pagination.query());
+
+        self.get(url, &query)
+            .await
+            .map_err(|err| err.with_resource("form", form_slug))
+    }
+
+    /// Stream every order of a form, see `stream_organization_orders`
+    pub fn stream_form_orders<'a>(
+        &'a self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+        filters: &OrderFilters,
+    ) -> impl Stream<Item = Result<Order>> + 'a {
+        let form = [organization_slug, form_slug].map(str::to_string);
+        let filters = filters.clone();
+
+        stream_pages(PaginationParams::page(1, PAGE_SIZE), move |params| {
+            let ([organization_slug, form_slug], filters) = (form.clone(), filters.clone());
+            let form_type = form_type.clone();
+            async move {
+                self.list_form_orders(&organization_slug, form_type, &form_slug, &filters, &params)
+                    .await
+            }
+        })
+    }
+
+    /// Get every order of a form, see `fetch_all_organization_orders`
+    pub async fn fetch_all_form_orders(
+        &self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+        filters: &OrderFilters,
+        max_in_flight: usize,
+    ) -> Result<Vec<Order>> {
+        fetch_all_concurrent(PAGE_SIZE, max_in_flight, |params| {
+            let form_type = form_type.clone();
+            async move {
+                self.list_form_orders(organization_slug, form_type, form_slug, filters, &params)
+                    .await
+            }
+        })
+        .await
+    }
+}
+
+/// An item of an [Order](crate::Order)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderItem {
+    pub id: u64,
+    pub name: Option<String>,
+    pub amount: Amount,
+    /// The tier of the form this item was bought from
+    pub tier_id: Option<u64>,
+    #[serde(rename = "type")]
+    pub item_type: ItemType,
+    /// Whether the tier of this item is eligible to tax receipt
+    #[serde(default)]
+    pub is_eligible_tax_receipt: bool,
+    /// The person this item is for, ie the participant of an event
+    pub user: Option<User>,
+    /// Answers to the custom questions of the form, in the form order
+    #[serde(default)]
+    pub custom_fields: Vec<CustomField>,
+    /// The extra options chosen with this item
+    #[serde(default)]
+    pub options: Vec<OrderItemOption>,
+}
+
+/// An extra option chosen with an [OrderItem](crate::OrderItem), ie a meal with an event ticket
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrderItemOption {
+    pub option_id: Option<u64>,
+    pub name: Option<String>,
+    #[serde(default)]
+    pub amount: Amount,
+    #[serde(default)]
+    pub is_required: bool,
+    /// Answers to the custom questions of the option
+    #[serde(default)]
+    pub custom_fields: Vec<CustomField>,
+}
+
+/// The person who paid an [Order](crate::Order)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payer {
+    pub email: Option<String>,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub address: Option<String>,
+    pub city: Option<String>,
+    pub zip_code: Option<String>,
+    /// Country code, ie `FRA`
+    pub country: Option<String>,
+    /// The company of the payer, when paying on behalf of a company
+    pub company: Option<String>,
+    pub date_of_birth: Option<DateTime<Utc>>,
+}
+
+/// The beneficiary of an [OrderItem](crate::OrderItem)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct User {
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+}
+
+/// The answer to a custom question of a form
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomField {
+    pub id: Option<u64>,
+    /// The question
+    pub name: String,
+    /// The kind of question, ie `TextInput` or `ChoiceList`
+    #[serde(rename = "type")]
+    pub field_type: Option<String>,
+    pub answer: String,
+}
+
+/// The kind of tier an item was bought from
+///
+/// The api adds tier types, an unknown type is kept as [Other](crate::ItemType::Other).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Name", into = "String")]
+pub enum ItemType {
+    Donation,
+    Payment,
+    Registration,
+    Membership,
+    MonthlyDonation,
+    MonthlyPayment,
+    OfflineDonation,
+    Contribution,
+    Bonus,
+    Product,
+    Other(String),
+}
+
+impl ItemType {
+    /// The name of the tier type in the api, used by the filters
+    pub fn as_str(&self) -> &str {
+        match self {
+            ItemType::Donation => "Donation",
+            ItemType::Payment => "Payment",
+            ItemType::Registration => "Registration",
+            ItemType::Membership => "Membership",
+            ItemType::MonthlyDonation => "MonthlyDonation",
+            ItemType::MonthlyPayment => "MonthlyPayment",
+            ItemType::OfflineDonation => "OfflineDonation",
+            ItemType::Contribution => "Contribution",
+            ItemType::Bonus => "Bonus",
+            ItemType::Product => "Product",
+            ItemType::Other(item_type) => item_type,
+        }
+    }
+
+    /// Whether this item is a donation
+    pub fn is_donation(&self) -> bool {
+        matches!(
+            self,
+            ItemType::Donation | ItemType::MonthlyDonation | ItemType::OfflineDonation
+        )
+    }
+}
+
+impl From<String> for ItemType {
+    fn from(item_type: String) -> Self {
+        match item_type.as_str() {
+            "Donation" => ItemType::Donation,
+            "Payment" => ItemType::Payment,
+            "Registration" => ItemType::Registration,
+            "Membership" => ItemType::Membership,
+            "MonthlyDonation" => ItemType::MonthlyDonation,
+            "MonthlyPayment" => ItemType::MonthlyPayment,
+            "OfflineDonation" => ItemType::OfflineDonation,
+            "Contribution" => ItemType::Contribution,
+            "Bonus" => ItemType::Bonus,
+            "Product" => ItemType::Product,
+            _ => ItemType::Other(item_type),
+        }
+    }
+}
+
+impl TryFrom<Name> for ItemType {
+    type Error = String;
+
+    fn try_from(name: Name) -> std::result::Result<Self, String> {
+        match ItemType::from(name.0) {
+            ItemType::Other(item_type) if is_strict() => Err(unknown_value(&item_type)),
+            item_type => Ok(item_type),
+        }
+    }
+}
+
+impl From<ItemType> for String {
+    fn from(item_type: ItemType) -> Self {
+        match item_type {
+            ItemType::Other(item_type) => item_type,
+            item_type => item_type.as_str().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_util::{self, MockApi, ORDER_ID},
+        Amount, Error, ItemType, Order, OrderCancellation, OrderDetail, OrderFilters, PaymentState,
+        SortOrder,
+    };
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+    use wiremock::{
+        matchers::{body_json, method, path},
+        Mock, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn order_not_found() {
+        let api = MockApi::start().await;
+        let client = api.client().await.unwrap();
+
+        assert!(client.get_order(ORDER_ID).await.is_ok());
+
+        let order = client.get_order(1).await;
+        assert!(
+            matches!(&order, Err(Error::NotFound { resource: "order", id, .. }) if id == "1"),
+            "{order:?}"
+        );
+    }
+
+    #[tokio::test]
+    async fn order_with_meta() {
+        let api = MockApi::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!("/v5/orders/{ORDER_ID}")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("x-request-id", "0HMV8Q3K1")
+                    .insert_header("x-ratelimit-remaining", "42")
+                    .set_body_json(test_util::order()),
+            )
+            .with_priority(1)
+            .mount(api.server())
+            .await;
+        let client = api.client().await.unwrap();
+
+        let (order, meta) = client.get_order_with_meta(ORDER_ID).await.unwrap();
+
+        assert_eq!(order.id, ORDER_ID);
+        assert_eq!(meta.request_id.as_deref(), Some("0HMV8Q3K1"));
+        assert_eq!(meta.rate_limit_remaining, Some(42));
+    }
+
+    #[test]
+    fn unknown_item_type() {
+        let item_type: ItemType = serde_json::from_str("\"NewTierType\"").unwrap();
+
+        assert_eq!(item_type, ItemType::Other("NewTierType".to_string()));
+    }
+
+    #[test]
+    fn deserialize_order() {
+        let order: Order = serde_json::from_value(json!({
+            "id": 12,
+            "date": "2023-06-01T10:00:00+02:00",
+            "amount": { "total": 3000, "vat": 0, "discount": 0 },
+            "payer": { "email": "jane@example.com", "firstName": "Jane", "lastName": "Doe" },
+            "items": [{ "id": 1, "amount": 3000, "type": "Membership" }],
+            "payments": [{ "id": 42, "amount": 3000, "state": "Authorized" }]
+        }))
+        .unwrap();
+
+        assert_eq!(order.amount.unwrap().total, Amount::from_cents(3000));
+        assert_eq!(order.payments[0].state, Some(PaymentState::Authorized));
+    }
+
+    #[test]
+    fn deserialize_order_detail() {
+        let order: OrderDetail = serde_json::from_value(json!({
+            "id": 12,
+            "payer": { "email": "jane@example.com", "city": "Lyon", "dateOfBirth": "1990-05-17T00:00:00+02:00" },
+            "items": [{
+                "id": 1,
+                "amount": 2500,
+                "tierId": 3,
+                "type": "Registration",
+                "customFields": [{ "id": 8, "name": "Régime", "type": "ChoiceList", "answer": "Végétarien" }],
+                "options": [{ "optionId": 5, "name": "Repas", "amount": 1500, "isRequired": false }]
+            }]
+        }))
+        .unwrap();
+
+        let item = &order.items[0];
+        assert_eq!(item.tier_id, Some(3));
+        assert_eq!(
+            item.custom_fields[0].field_type.as_deref(),
+            Some("ChoiceList")
+        );
+        assert_eq!(item.options[0].amount, Amount::from_cents(1500));
+        assert_eq!(order.payer.unwrap().city.as_deref(), Some("Lyon"));
+    }
+
+    #[tokio::test]
+    async fn cancel_order_body() {
+        let api = MockApi::start().await;
+        Mock::given(method("POST"))
+            .and(path(format!("/v5/orders/{ORDER_ID}/cancel")))
+            .and(body_json(json!({})))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "payments": [{ "id": 43, "amount": 1000, "state": "Canceled" }]
+            })))
+            .expect(1)
+            .mount(api.server())
+            .await;
+        let client = api.client().await.unwrap();
+
+        let cancellation = client.cancel_order(ORDER_ID).await.unwrap();
+
+        assert_eq!(cancellation.payments[0].id, 43);
+    }
+
+    #[test]
+    fn deserialize_order_cancellation() {
+        let cancellation: OrderCancellation = serde_json::from_value(json!({
+            "payments": [{ "id": 43, "amount": 1000, "state": "Canceled" }]
+        }))
+        .unwrap();
+
+        assert_eq!(cancellation.payments[0].state, Some(PaymentState::Canceled));
+    }
+
+    #[test]
+    fn sort_order_query() {
+        assert!(OrderFilters::default().query().is_empty());
+
+        let filters = OrderFilters {
+            sort_order: Some(SortOrder::Desc),
+            ..Default::default()
+        };
+        assert_eq!(filters.query(), vec![("sortOrder", "Desc".to_string())]);
+    }
+
+    #[test]
+    fn order_filters_query() {
+        let filters = OrderFilters::default()
+            .with_from(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap())
+            .with_to(Utc.with_ymd_and_hms(2023, 12, 31, 0, 0, 0).unwrap())
+            .with_item_type(ItemType::Membership)
+            .with_details()
+            .with_count();
+
+        assert_eq!(
+            filters.query(),
+            vec![
+                ("tierTypes", "Membership".to_string()),
+                ("from", "2023-01-01T00:00:00+00:00".to_string()),
+                ("to", "2023-12-31T00:00:00+00:00".to_string()),
+                ("withDetails", "true".to_string()),
+                ("withCount", "true".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn order_filters_other_item_type() {
+        let filters =
+            OrderFilters::default().with_item_type(ItemType::Other("NewTierType".to_string()));
+
+        assert_eq!(
+            filters.query(),
+            vec![("tierTypes", "NewTierType".to_string())]
+        );
+    }
+
+    #[test]
+    fn eligible_receipt_amount_mixed_order() {
+        let order: Order = serde_json::from_value(json!({
+            "id": 12,
+            "formSlug": "gala",
+            "formType": "Event",
+            "organizationSlug": "my-asso",
+            "items": [
+                {
+                    "id": 1,
+                    "name": "Don",
+                    "amount": 5000,
+                    "type": "Donation",
+                    "isEligibleTaxReceipt": true
+                },
+                {
+                    "id": 2,
+                    "name": "Ticket",
+                    "amount": 2500,
+                    "type": "Registration",
+                    "isEligibleTaxReceipt": true
+                },
+                {
+                    "id": 3,
+                    "name": "Don sans reçu",
+                    "amount": 1000,
+                    "type": "Donation"
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(order.eligible_receipt_amount(), Amount::from_cents(5000));
+    }
+}
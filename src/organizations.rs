@@ -0,0 +1,532 @@
+//! `organizations` the organizations (non profit) registered on helloasso
+
+use std::str::FromStr;
+
+use futures_util::{stream, StreamExt, TryStreamExt};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::{
+    decode::{is_strict, unknown_value, Name},
+    Error, FormLightModel, FormsFilters, HelloAsso, Result,
+};
+
+/// An organization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Organization {
+    pub organization_slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub city: Option<String>,
+    pub zip_code: Option<String>,
+    pub address: Option<String>,
+    pub logo: Option<String>,
+    pub banner: Option<String>,
+    /// Url of the organization page on helloasso
+    pub url: Option<String>,
+    #[serde(rename = "type")]
+    pub organization_type: Option<String>,
+    pub category: Option<OrganizationCategory>,
+    pub geolocation: Option<GeoLocation>,
+    pub rna_number: Option<String>,
+    /// Whether the organization can issue fiscal receipts
+    pub fiscal_receipt_eligibility: Option<bool>,
+    /// Whether the organization issues fiscal receipts through helloasso
+    pub fiscal_receipt_issuance_enabled: Option<bool>,
+    /// Whether the organization identity was verified by helloasso
+    pub is_authenticated: Option<bool>,
+}
+
+/// The category of an organization
+///
+/// The list of categories evolve, an unknown category is kept as [Other](crate::OrganizationCategory::Other).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Name", into = "String")]
+pub enum OrganizationCategory {
+    Sport,
+    Culture,
+    Education,
+    Humanitarian,
+    Health,
+    Environment,
+    Social,
+    Leisure,
+    Religion,
+    Other(String),
+}
+
+impl OrganizationCategory {
+    /// The name of the category as used by the api
+    pub fn as_str(&self) -> &str {
+        match self {
+            OrganizationCategory::Sport => "Sport",
+            OrganizationCategory::Culture => "Culture",
+            OrganizationCategory::Education => "Education",
+            OrganizationCategory::Humanitarian => "Humanitarian",
+            OrganizationCategory::Health => "Health",
+            OrganizationCategory::Environment => "Environment",
+            OrganizationCategory::Social => "Social",
+            OrganizationCategory::Leisure => "Leisure",
+            OrganizationCategory::Religion => "Religion",
+            OrganizationCategory::Other(category) => category,
+        }
+    }
+}
+
+impl From<String> for OrganizationCategory {
+    fn from(category: String) -> Self {
+        match category.as_str() {
+            "Sport" => OrganizationCategory::Sport,
+            "Culture" => OrganizationCategory::Culture,
+            "Education" => OrganizationCategory::Education,
+            "Humanitarian" => OrganizationCategory::Humanitarian,
+            "Health" => OrganizationCategory::Health,
+            "Environment" => OrganizationCategory::Environment,
+            "Social" => OrganizationCategory::Social,
+            "Leisure" => OrganizationCategory::Leisure,
+            "Religion" => OrganizationCategory::Religion,
+            _ => OrganizationCategory::Other(category),
+        }
+    }
+}
+
+impl TryFrom<Name> for OrganizationCategory {
+    type Error = String;
+
+    fn try_from(name: Name) -> std::result::Result<Self, String> {
+        match OrganizationCategory::from(name.0) {
+            OrganizationCategory::Other(category) if is_strict() => Err(unknown_value(&category)),
+            category => Ok(category),
+        }
+    }
+}
+
+impl From<OrganizationCategory> for String {
+    fn from(category: OrganizationCategory) -> Self {
+        match category {
+            OrganizationCategory::Other(category) => category,
+            category => category.as_str().to_string(),
+        }
+    }
+}
+
+/// Coordinates of a place
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GeoLocation {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+/// The public profile of an organization, as displayed on its helloasso page
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationProfile {
+    pub organization_slug: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub logo: Option<String>,
+    pub website_url: Option<String>,
+    #[serde(default)]
+    pub social_networks: Vec<SocialLink>,
+    #[serde(rename = "banner")]
+    pub cover_image: Option<String>,
+    /// The forms currently published by the organization
+    #[serde(default)]
+    pub active_forms: Vec<FormLightModel>,
+}
+
+/// A link to a social network page of an organization
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SocialLink {
+    pub name: String,
+    pub url: String,
+}
+
+/// The legal information of an organization
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationLegalInfo {
+    pub legal_status: Option<String>,
+    /// The legal category of the organization in the french registry, ie `9220` for a declared association
+    pub legal_category: Option<String>,
+    pub rna_number: Option<String>,
+    /// `None` when the organization has no siret, or a malformed one in lenient mode
+    #[serde(default, deserialize_with = "deserialize_siret")]
+    pub siret: Option<Siret>,
+    /// Whether the organization can issue fiscal receipts
+    pub fiscal_receipt_eligibility: Option<bool>,
+    /// The registered address of the organization
+    pub address: Option<Address>,
+    /// The address the organization receives its mail at, when it isn't the registered one
+    pub mailing_address: Option<Address>,
+}
+
+impl OrganizationLegalInfo {
+    /// The SIREN of the organization, see [Siret::siren](crate::Siret::siren)
+    pub fn siren(&self) -> Option<&str> {
+        self.siret.as_ref().map(Siret::siren)
+    }
+}
+
+/// A postal address
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Address {
+    pub address: Option<String>,
+    pub city: Option<String>,
+    pub zip_code: Option<String>,
+    pub country: Option<String>,
+}
+
+/// The siret of the legal information, the api sends an empty one to the organizations without it
+///
+/// A malformed siret is an error in strict mode only, the rest of the legal information
+/// is still readable in lenient mode.
+fn deserialize_siret<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> std::result::Result<Option<Siret>, D::Error> {
+    let siret = Option::<String>::deserialize(deserializer)?.filter(|siret| !siret.is_empty());
+
+    match siret.map(Siret::new) {
+        Some(Err(err)) if is_strict() => Err(serde::de::Error::custom(err)),
+        siret => Ok(siret.and_then(Result::ok)),
+    }
+}
+
+/// A SIRET number, the 14 digits identifying an establishment in France
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Siret(String);
+
+impl Siret {
+    /// Check that `siret` is made of 14 digits
+    pub fn new(siret: impl Into<String>) -> Result<Self> {
+        Siret::try_from(siret.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The SIREN, the 9 first digits identifying the organization itself
+    pub fn siren(&self) -> &str {
+        &self.0[..9]
+    }
+}
+
+impl TryFrom<String> for Siret {
+    type Error = Error;
+
+    fn try_from(siret: String) -> Result<Self> {
+        if siret.len() == 14 && siret.chars().all(|c| c.is_ascii_digit()) {
+            Ok(Siret(siret))
+        } else {
+            Err(Error::InvalidInput(format!(
+                "a siret is made of 14 digits, got {siret:?}"
+            )))
+        }
+    }
+}
+
+impl FromStr for Siret {
+    type Err = Error;
+
+    fn from_str(siret: &str) -> Result<Self> {
+        Siret::new(siret)
+    }
+}
+
+impl From<Siret> for String {
+    fn from(siret: Siret) -> Self {
+        siret.0
+    }
+}
+
+impl HelloAsso {
+    /// Get an organization
+    ///
+    /// ```rust,no_run
+    /// # use helloasso::{HelloAsso, Error};
+    /// # #[tokio::main(flavor = "current_thread")]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = HelloAsso::new("client_id".to_string(), "client_secret".to_string()).await?;
+    /// let organization = client.get_organization("my-asso").await?;
+    /// println!("{} is located in {:?}", organization.name, organization.city);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_organization(&self, slug: &str) -> Result<Organization> {
+        let url = self.endpoint(&["organizations", slug]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("organization", slug))
+    }
+
+    /// Same as `get_organization` but return the json sent by the api, see `get_order_raw`
+    pub async fn get_organization_raw(&self, slug: &str) -> Result<Value> {
+        let url = self.endpoint(&["organizations", slug]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("organization", slug))
+    }
+
+    /// Get several organizations, at most `concurrency` at a time
+    ///
+    /// Each organization has its own result, a failure doesn't stop the others.
+    /// The results are in completion order.
+    pub async fn get_organizations(
+        &self,
+        slugs: &[&str],
+        concurrency: usize,
+    ) -> Vec<(String, Result<Organization>)> {
+        stream::iter(slugs)
+            .map(|slug| async move { (slug.to_string(), self.get_organization(slug).await) })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Get the legal information of an organization
+    ///
+    /// Those information are sensitive, a client without the right permission
+    /// gets an [Error::PermErr](crate::Error::PermErr).
+    pub async fn get_organization_legal_info(&self, slug: &str) -> Result<OrganizationLegalInfo> {
+        let url = self.endpoint(&["organizations", slug, "legal-informations"]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("organization", slug))
+    }
+
+    /// Get the public profile of an organization along with its published forms
+    ///
+    /// A private or unpublished organization returns an [Error::NotFound](crate::Error::NotFound).
+    pub async fn get_organization_public_profile(&self, slug: &str) -> Result<OrganizationProfile> {
+        let url = self.endpoint(&["organizations", slug]);
+        let mut profile = self
+            .get::<OrganizationProfile, _>(url, &())
+            .await
+            .map_err(|err| err.with_resource("organization", slug))?;
+
+        let filters = FormsFilters {
+            states: vec!["Public".to_string()],
+            ..Default::default()
+        };
+        profile.active_forms = self.stream_forms(slug, &filters).try_collect().await?;
+
+        Ok(profile)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        decode::decode_json,
+        test_util::{self, MockApi, ORGANIZATION_SLUG},
+        DecodeMode, Error, GeoLocation, Organization, OrganizationCategory, OrganizationLegalInfo,
+        OrganizationProfile, Siret,
+    };
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, ResponseTemplate,
+    };
+
+    #[test]
+    fn deserialize_organization() {
+        let organization: Organization = serde_json::from_value(json!({
+            "organizationSlug": "my-asso",
+            "name": "My asso",
+            "city": "Paris",
+            "type": "Association1901",
+            "category": "Sport",
+            "geolocation": { "latitude": 48.8566, "longitude": 2.3522 },
+            "fiscalReceiptEligibility": true,
+            "isAuthenticated": true
+        }))
+        .unwrap();
+
+        assert_eq!(organization.organization_slug, "my-asso");
+        assert_eq!(organization.category, Some(OrganizationCategory::Sport));
+        assert_eq!(organization.fiscal_receipt_eligibility, Some(true));
+        assert_eq!(
+            organization.geolocation,
+            Some(GeoLocation {
+                latitude: 48.8566,
+                longitude: 2.3522
+            })
+        );
+        assert_eq!(
+            organization.organization_type.as_deref(),
+            Some("Association1901")
+        );
+    }
+
+    #[tokio::test]
+    async fn organizations_results_per_slug() {
+        let api = MockApi::start().await;
+        let client = api.client().await.unwrap();
+
+        let mut organizations = client
+            .get_organizations(&[ORGANIZATION_SLUG, "unknown-asso"], 2)
+            .await;
+        organizations.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(organizations.len(), 2);
+        assert_eq!(organizations[0].0, ORGANIZATION_SLUG);
+        let organization = organizations[0].1.as_ref().unwrap();
+        assert_eq!(organization.organization_slug, ORGANIZATION_SLUG);
+        assert_eq!(organization.name, test_util::organization()["name"]);
+        assert_eq!(organizations[1].0, "unknown-asso");
+        assert!(
+            matches!(&organizations[1].1, Err(Error::NotFound { resource: "organization", id, .. }) if id == "unknown-asso"),
+            "{:?}",
+            organizations[1].1
+        );
+
+        let mut requests: Vec<_> = api
+            .server()
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|request| request.url.path().starts_with("/v5/"))
+            .map(|request| {
+                (
+                    request.url.path().to_string(),
+                    request.url.query().map(str::to_string),
+                )
+            })
+            .collect();
+        requests.sort();
+        assert_eq!(
+            requests,
+            [
+                (format!("/v5/organizations/{ORGANIZATION_SLUG}"), None),
+                ("/v5/organizations/unknown-asso".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_category() {
+        let category: OrganizationCategory = serde_json::from_str("\"Astronomy\"").unwrap();
+
+        assert_eq!(
+            category,
+            OrganizationCategory::Other("Astronomy".to_string())
+        );
+        assert_eq!(category.as_str(), "Astronomy");
+        assert_eq!(serde_json::to_string(&category).unwrap(), "\"Astronomy\"");
+    }
+
+    #[test]
+    fn deserialize_profile() {
+        let profile: OrganizationProfile = serde_json::from_value(json!({
+            "organizationSlug": "my-asso",
+            "name": "My asso",
+            "description": "A sport club",
+            "banner": "https://cdn.helloasso.com/banner.png",
+            "socialNetworks": [{ "name": "Mastodon", "url": "https://mastodon.social/@myasso" }]
+        }))
+        .unwrap();
+
+        assert_eq!(
+            profile.cover_image.as_deref(),
+            Some("https://cdn.helloasso.com/banner.png")
+        );
+        assert_eq!(profile.social_networks.len(), 1);
+        assert!(profile.active_forms.is_empty());
+    }
+
+    #[tokio::test]
+    async fn public_profile_every_form() {
+        let api = MockApi::start().await;
+        let forms_path = format!("/v5/organizations/{ORGANIZATION_SLUG}/forms");
+        for (page_index, form_slug) in [(1, "gala"), (2, "membership")] {
+            Mock::given(method("GET"))
+                .and(path(forms_path.as_str()))
+                .and(query_param("pageIndex", page_index.to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "data": [{ "formSlug": form_slug, "formType": "Event" }],
+                    "pagination": { "pageIndex": page_index, "pageSize": 1, "totalPages": 2 }
+                })))
+                .mount(api.server())
+                .await;
+        }
+        let client = api.client().await.unwrap();
+
+        let profile = client
+            .get_organization_public_profile(ORGANIZATION_SLUG)
+            .await
+            .unwrap();
+
+        let form_slugs: Vec<_> = profile
+            .active_forms
+            .iter()
+            .map(|form| form.form_slug.as_str())
+            .collect();
+        assert_eq!(form_slugs, ["gala", "membership"]);
+        assert_eq!(profile.name, test_util::organization()["name"]);
+    }
+
+    #[test]
+    fn deserialize_legal_info() {
+        let legal_info: OrganizationLegalInfo = serde_json::from_value(json!({
+            "legalStatus": "Association1901Rig",
+            "legalCategory": "9220",
+            "rnaNumber": "W751234567",
+            "siret": "12345678900011",
+            "fiscalReceiptEligibility": true,
+            "address": { "address": "1 rue de la Paix", "city": "Paris", "zipCode": "75002" },
+            "mailingAddress": { "address": "BP 12", "city": "Paris", "zipCode": "75001" }
+        }))
+        .unwrap();
+
+        assert_eq!(legal_info.siren(), Some("123456789"));
+        assert_eq!(legal_info.legal_category.as_deref(), Some("9220"));
+        assert_eq!(
+            legal_info
+                .mailing_address
+                .and_then(|address| address.zip_code),
+            Some("75001".to_string())
+        );
+        assert_eq!(legal_info.siret.unwrap().as_str(), "12345678900011");
+        assert_eq!(legal_info.fiscal_receipt_eligibility, Some(true));
+    }
+
+    #[test]
+    fn invalid_siret() {
+        assert!(Siret::new("1234").is_err());
+        assert!("1234567890001A".parse::<Siret>().is_err());
+        assert_eq!(
+            "12345678900011".parse::<Siret>().unwrap().siren(),
+            "123456789"
+        );
+    }
+
+    #[test]
+    fn decode_missing_or_malformed_siret() {
+        for siret in [json!(""), json!(null), json!("123")] {
+            let body = json!({ "legalCategory": "9220", "siret": siret }).to_string();
+            let legal_info =
+                decode_json::<OrganizationLegalInfo>(body.as_bytes(), DecodeMode::Lenient).unwrap();
+
+            assert_eq!(legal_info.siret, None);
+            assert_eq!(legal_info.legal_category.as_deref(), Some("9220"));
+        }
+
+        let legal_info: OrganizationLegalInfo = serde_json::from_value(json!({})).unwrap();
+        assert_eq!(legal_info.siret, None);
+
+        let body = json!({ "siret": "" }).to_string();
+        assert!(decode_json::<OrganizationLegalInfo>(body.as_bytes(), DecodeMode::Strict).is_ok());
+        let body = json!({ "siret": "123" }).to_string();
+        let malformed = decode_json::<OrganizationLegalInfo>(body.as_bytes(), DecodeMode::Strict);
+        assert!(
+            matches!(&malformed, Err(Error::DecodeErr(err)) if err.path().to_string() == "siret"),
+            "{malformed:?}"
+        );
+    }
+}
@@ -0,0 +1,349 @@
+//! `pagination` the pages returned by the list endpoints
+
+use std::future::Future;
+
+use futures_util::{stream, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+
+use crate::Result;
+
+/// A page of elements returned by a list endpoint
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Paginated<T> {
+    pub data: Vec<T>,
+    /// Position of this page, missing on the endpoints returning everything at once
+    pub pagination: Option<Pagination>,
+}
+
+impl<T> Paginated<T> {
+    /// The parameters to request the page after this one, none on the last page
+    ///
+    /// The continuation token is used when the api sends one, the page index otherwise.
+    pub fn next_page(&self) -> Option<PaginationParams> {
+        let pagination = self.pagination.as_ref()?;
+        if self.data.is_empty() {
+            return None;
+        }
+
+        match &pagination.continuation_token {
+            Some(token) if !token.is_empty() => Some(PaginationParams {
+                page_index: None,
+                page_size: Some(pagination.page_size),
+                continuation_token: Some(token.clone()),
+            }),
+            _ if pagination.page_index < pagination.total_pages => Some(PaginationParams {
+                page_index: Some(pagination.page_index + 1),
+                page_size: Some(pagination.page_size),
+                continuation_token: None,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// The position of a [Paginated](crate::Paginated) page
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    /// Index of the page, starting at 1
+    #[serde(default)]
+    pub page_index: u32,
+    #[serde(default)]
+    pub page_size: u32,
+    #[serde(default)]
+    pub total_pages: u32,
+    /// Total number of elements, across all the pages
+    #[serde(default)]
+    pub total_count: u64,
+    /// Token to request the next page
+    pub continuation_token: Option<String>,
+}
+
+/// The page requested from a list endpoint
+///
+/// Unset fields are not sent, the api default are used.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaginationParams {
+    /// Index of the page, starting at 1
+    pub page_index: Option<u32>,
+    pub page_size: Option<u32>,
+    /// Token of the page, given by the previous page
+    pub continuation_token: Option<String>,
+}
+
+impl PaginationParams {
+    /// Request the page `page_index` with `page_size` elements
+    pub fn page(page_index: u32, page_size: u32) -> Self {
+        PaginationParams {
+            page_index: Some(page_index),
+            page_size: Some(page_size),
+            continuation_token: None,
+        }
+    }
+
+    pub(crate) fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = Vec::new();
+        if let Some(page_index) = self.page_index {
+            query.push(("pageIndex", page_index.to_string()));
+        }
+        if let Some(page_size) = self.page_size {
+            query.push(("pageSize", page_size.to_string()));
+        }
+        if let Some(continuation_token) = &self.continuation_token {
+            query.push(("continuationToken", continuation_token.clone()));
+        }
+        query
+    }
+}
+
+/// Stream the elements of every page, starting from `first_page`
+///
+/// The next page is fetched once the elements of the current one are consumed.
+/// The stream ends after the first error.
+pub(crate) fn stream_pages<'a, T, F, Fut>(
+    first_page: PaginationParams,
+    fetch: F,
+) -> impl Stream<Item = Result<T>> + 'a
+where
+    T: 'a,
+    F: Fn(PaginationParams) -> Fut + 'a,
+    Fut: Future<Output = Result<Paginated<T>>> + 'a,
+{
+    stream::unfold(Some(first_page), move |params| {
+        let page = params.map(&fetch);
+        async move {
+            match page?.await {
+                Ok(page) => {
+                    let next_page = page.next_page();
+                    let elements = page.data.into_iter().map(Ok).collect::<Vec<_>>();
+                    Some((stream::iter(elements), next_page))
+                }
+                Err(err) => Some((stream::iter(vec![Err(err)]), None)),
+            }
+        }
+    })
+    .flatten()
+}
+
+/// Fetch every page, up to `max_in_flight` at once, the elements are in the order of the pages
+///
+/// The first page tells how many pages there are, the others are requested by index.
+/// The pages after the last one are then followed one by one, ie pages added since
+/// the first one or an api paginating with continuation tokens only.
+pub(crate) async fn fetch_all_concurrent<T, F, Fut>(
+    page_size: u32,
+    max_in_flight: usize,
+    fetch: F,
+) -> Result<Vec<T>>
+where
+    F: Fn(PaginationParams) -> Fut,
+    Fut: Future<Output = Result<Paginated<T>>>,
+{
+    let first_page = fetch(PaginationParams::page(1, page_size)).await?;
+    let total_pages = first_page
+        .pagination
+        .as_ref()
+        .map_or(1, |pagination| pagination.total_pages);
+
+    let mut next_page = first_page.next_page();
+    let mut elements = first_page.data;
+    let mut pages = stream::iter(2..=total_pages)
+        .map(|page_index| fetch(PaginationParams::page(page_index, page_size)))
+        .buffered(max_in_flight.max(1));
+    while let Some(page) = pages.next().await {
+        let page = page?;
+        next_page = page.next_page();
+        elements.extend(page.data);
+    }
+
+    while let Some(params) = next_page {
+        let page = fetch(params).await?;
+        next_page = page.next_page();
+        elements.extend(page.data);
+    }
+
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::{fetch_all_concurrent, stream_pages};
+    use crate::{Error, Paginated, Pagination, PaginationParams};
+    use futures_util::StreamExt;
+    use serde_json::json;
+
+    #[test]
+    fn next_page_by_index() {
+        let page: Paginated<u32> = serde_json::from_value(json!({
+            "data": [1, 2],
+            "pagination": { "pageIndex": 1, "pageSize": 2, "totalPages": 2, "totalCount": 3 }
+        }))
+        .unwrap();
+
+        assert_eq!(page.next_page(), Some(PaginationParams::page(2, 2)));
+    }
+
+    #[test]
+    fn next_page_by_continuation_token() {
+        let page: Paginated<u32> = serde_json::from_value(json!({
+            "data": [1, 2],
+            "pagination": { "pageIndex": 1, "pageSize": 2, "continuationToken": "abc" }
+        }))
+        .unwrap();
+        let next_page = page.next_page().unwrap();
+
+        assert_eq!(
+            next_page.query(),
+            vec![
+                ("pageSize", "2".to_string()),
+                ("continuationToken", "abc".to_string())
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_every_page() {
+        let pages = stream_pages(PaginationParams::page(1, 2), |params| async move {
+            let page_index = params.page_index.unwrap();
+            Ok(Paginated {
+                data: vec![page_index * 10, page_index * 10 + 1],
+                pagination: Some(Pagination {
+                    page_index,
+                    page_size: 2,
+                    total_pages: 3,
+                    ..Default::default()
+                }),
+            })
+        });
+
+        let elements = pages.map(Result::unwrap).collect::<Vec<_>>().await;
+
+        assert_eq!(elements, vec![10, 11, 20, 21, 30, 31]);
+    }
+
+    #[tokio::test]
+    async fn stream_stops_on_error() {
+        let pages = stream_pages(PaginationParams::page(1, 2), |_| async {
+            Err::<Paginated<u32>, _>(Error::InvalidInput("invalid page".to_string()))
+        });
+
+        let elements = pages.collect::<Vec<_>>().await;
+
+        assert_eq!(elements.len(), 1);
+        assert!(matches!(elements[0], Err(Error::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn fetch_pages_concurrently() {
+        let (in_flight, max_in_flight) = (AtomicUsize::new(0), AtomicUsize::new(0));
+
+        let elements = fetch_all_concurrent(2, 2, |params| {
+            let (in_flight, max_in_flight) = (&in_flight, &max_in_flight);
+            async move {
+                let page_index = params.page_index.unwrap();
+                let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                max_in_flight.fetch_max(current, Ordering::SeqCst);
+                // The later pages answer first
+                tokio::time::sleep(Duration::from_millis(50 / u64::from(page_index))).await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+
+                Ok(Paginated {
+                    data: vec![page_index * 10, page_index * 10 + 1],
+                    pagination: Some(Pagination {
+                        page_index,
+                        page_size: 2,
+                        total_pages: 4,
+                        ..Default::default()
+                    }),
+                })
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(elements, vec![10, 11, 20, 21, 30, 31, 40, 41]);
+        assert_eq!(max_in_flight.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn fetch_concurrently_pages_added() {
+        // A page is added while the pages are fetched
+        let elements = fetch_all_concurrent(2, 2, |params| async move {
+            let page_index = params.page_index.unwrap();
+            Ok(Paginated {
+                data: vec![page_index],
+                pagination: Some(Pagination {
+                    page_index,
+                    total_pages: if page_index == 1 { 2 } else { 3 },
+                    ..Default::default()
+                }),
+            })
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(elements, vec![1, 2, 3]);
+    }
+
+    #[tokio::test]
+    async fn fetch_concurrently_continuation_token() {
+        let elements = fetch_all_concurrent(2, 2, |params| async move {
+            let (data, token) = match params.continuation_token.as_deref() {
+                None => (vec![1, 2], "a"),
+                Some("a") => (vec![3, 4], "b"),
+                _ => (vec![], "c"),
+            };
+            Ok(Paginated {
+                data,
+                pagination: Some(Pagination {
+                    page_index: 1,
+                    page_size: 2,
+                    continuation_token: Some(token.to_string()),
+                    ..Default::default()
+                }),
+            })
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(elements, vec![1, 2, 3, 4]);
+    }
+
+    #[tokio::test]
+    async fn fetch_concurrently_fails_on_error() {
+        let elements = fetch_all_concurrent(2, 4, |params| async move {
+            let page_index = params.page_index.unwrap();
+            if page_index == 3 {
+                return Err(Error::InvalidInput("invalid page".to_string()));
+            }
+            Ok(Paginated {
+                data: vec![page_index],
+                pagination: Some(Pagination {
+                    page_index,
+                    total_pages: 4,
+                    ..Default::default()
+                }),
+            })
+        })
+        .await;
+
+        assert!(matches!(elements, Err(Error::InvalidInput(_))));
+    }
+
+    #[test]
+    fn last_page() {
+        let page: Paginated<u32> = serde_json::from_value(json!({
+            "data": [1],
+            "pagination": { "pageIndex": 2, "pageSize": 2, "totalPages": 2 }
+        }))
+        .unwrap();
+
+        assert_eq!(page.next_page(), None);
+        assert!(PaginationParams::default().query().is_empty());
+    }
+}
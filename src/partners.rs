@@ -0,0 +1,183 @@
+//! `partners` the partner account of the client and its notification urls
+
+use serde::{Deserialize, Serialize};
+
+use crate::{HelloAsso, Result};
+
+/// The partner account the client belongs to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Partner {
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub url: Option<String>,
+    pub logo: Option<String>,
+    /// The urls helloasso sends the notifications to
+    #[serde(default)]
+    pub url_notification_list: Vec<NotificationUrl>,
+}
+
+/// An url helloasso sends notifications to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationUrl {
+    pub url: String,
+    /// The notifications sent to this url, every notification when none
+    pub api_notification_type: Option<NotificationType>,
+}
+
+/// The kind of notification sent to a [NotificationUrl](crate::NotificationUrl)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NotificationType {
+    Order,
+    Payment,
+    Form,
+    Organization,
+}
+
+impl NotificationType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NotificationType::Order => "Order",
+            NotificationType::Payment => "Payment",
+            NotificationType::Form => "Form",
+            NotificationType::Organization => "Organization",
+        }
+    }
+}
+
+/// The body of a notification url configuration
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct NotificationUrlRequest<'a> {
+    url: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    notification_type: Option<NotificationType>,
+}
+
+fn notification_type_query(
+    notification_type: Option<NotificationType>,
+) -> Vec<(&'static str, &'static str)> {
+    notification_type
+        .iter()
+        .map(|notification_type| ("notificationType", notification_type.as_str()))
+        .collect()
+}
+
+impl HelloAsso {
+    /// Get the partner account of the client
+    pub async fn get_partner(&self) -> Result<Partner> {
+        let url = self.endpoint(&["partners", "me"]);
+        self.get(url, &()).await
+    }
+
+    /// Set the url the notifications of every organization are sent to
+    ///
+    /// Without `notification_type` every notification is sent to `notification_url`.
+    pub async fn set_notification_url(
+        &self,
+        notification_url: &str,
+        notification_type: Option<NotificationType>,
+    ) -> Result<NotificationUrl> {
+        let url = self.endpoint(&["partners", "me", "api-notifications"]);
+        let body = NotificationUrlRequest {
+            url: notification_url,
+            notification_type,
+        };
+        self.put(url, &(), &body).await
+    }
+
+    /// Stop sending the notifications of every organization
+    pub async fn remove_notification_url(
+        &self,
+        notification_type: Option<NotificationType>,
+    ) -> Result<()> {
+        let url = self.endpoint(&["partners", "me", "api-notifications"]);
+        self.delete(url, &notification_type_query(notification_type))
+            .await
+    }
+
+    /// Set the url the notifications of an organization are sent to
+    pub async fn set_organization_notification_url(
+        &self,
+        organization_slug: &str,
+        notification_url: &str,
+        notification_type: Option<NotificationType>,
+    ) -> Result<NotificationUrl> {
+        let url = self.endpoint(&[
+            "partners",
+            "me",
+            "api-notifications",
+            "organizations",
+            organization_slug,
+        ]);
+        let body = NotificationUrlRequest {
+            url: notification_url,
+            notification_type,
+        };
+        self.put(url, &(), &body)
+            .await
+            .map_err(|err| err.with_resource("organization", organization_slug))
+    }
+
+    /// Stop sending the notifications of an organization
+    pub async fn remove_organization_notification_url(
+        &self,
+        organization_slug: &str,
+        notification_type: Option<NotificationType>,
+    ) -> Result<()> {
+        let url = self.endpoint(&[
+            "partners",
+            "me",
+            "api-notifications",
+            "organizations",
+            organization_slug,
+        ]);
+        self.delete(url, &notification_type_query(notification_type))
+            .await
+            .map_err(|err| err.with_resource("organization", organization_slug))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{notification_type_query, NotificationUrlRequest};
+    use crate::{NotificationType, Partner};
+    use serde_json::json;
+
+    #[test]
+    fn deserialize_partner() {
+        let partner: Partner = serde_json::from_value(json!({
+            "displayName": "My partner",
+            "urlNotificationList": [
+                { "url": "https://example.com/notifications" },
+                { "url": "https://example.com/payments", "apiNotificationType": "Payment" }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(partner.url_notification_list.len(), 2);
+        assert_eq!(
+            partner.url_notification_list[1].api_notification_type,
+            Some(NotificationType::Payment)
+        );
+    }
+
+    #[test]
+    fn notification_url_request() {
+        let body = NotificationUrlRequest {
+            url: "https://example.com/notifications",
+            notification_type: None,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&body).unwrap(),
+            json!({ "url": "https://example.com/notifications" })
+        );
+        assert!(notification_type_query(None).is_empty());
+        assert_eq!(
+            notification_type_query(Some(NotificationType::Order)),
+            vec![("notificationType", "Order")]
+        );
+    }
+}
@@ -0,0 +1,726 @@
+//! `payments` the payments received by an organization
+
+use chrono::{DateTime, Utc};
+use futures_util::Stream;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    client::PAGE_SIZE,
+    decode::{is_strict, unknown_value, Name},
+    pagination::{fetch_all_concurrent, stream_pages},
+    Amount, Error, FormType, HelloAsso, Order, Paginated, PaginationParams, Payer, ResponseMeta,
+    Result, SortOrder,
+};
+
+/// A payment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payment {
+    pub id: u64,
+    pub amount: Amount,
+    pub date: Option<DateTime<Utc>>,
+    pub state: Option<PaymentState>,
+    pub payment_means: Option<PaymentMeans>,
+    /// Whether the money was transferred to the organization bank account
+    pub cash_out_state: Option<CashOutState>,
+    pub payer: Option<Payer>,
+    /// The order this payment is for, a standalone payment has none
+    pub order: Option<PaymentOrder>,
+}
+
+/// The state of a [Payment](crate::Payment)
+///
+/// The api adds states, an unknown state is kept as [Other](crate::PaymentState::Other).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Name", into = "String")]
+pub enum PaymentState {
+    Pending,
+    /// The payment is authorized but not captured yet
+    ///
+    /// Helloasso captures the authorized payments by itself, the api has no endpoint
+    /// to capture them.
+    Authorized,
+    Refused,
+    Registered,
+    Error,
+    Refunded,
+    Refunding,
+    /// The payment is waiting for the payer, ie a check that wasn't received yet
+    Waiting,
+    Canceled,
+    Contested,
+    WaitingBankValidation,
+    WaitingBankWithdraw,
+    /// The payer left the checkout before paying
+    Abandoned,
+    Other(String),
+}
+
+impl PaymentState {
+    /// The name of the state in the api, used by the filters
+    pub fn as_str(&self) -> &str {
+        match self {
+            PaymentState::Pending => "Pending",
+            PaymentState::Authorized => "Authorized",
+            PaymentState::Refused => "Refused",
+            PaymentState::Registered => "Registered",
+            PaymentState::Error => "Error",
+            PaymentState::Refunded => "Refunded",
+            PaymentState::Refunding => "Refunding",
+            PaymentState::Waiting => "Waiting",
+            PaymentState::Canceled => "Canceled",
+            PaymentState::Contested => "Contested",
+            PaymentState::WaitingBankValidation => "WaitingBankValidation",
+            PaymentState::WaitingBankWithdraw => "WaitingBankWithdraw",
+            PaymentState::Abandoned => "Abandoned",
+            PaymentState::Other(state) => state,
+        }
+    }
+}
+
+impl From<String> for PaymentState {
+    fn from(state: String) -> Self {
+        match state.as_str() {
+            "Pending" => PaymentState::Pending,
+            "Authorized" => PaymentState::Authorized,
+            "Refused" => PaymentState::Refused,
+            "Registered" => PaymentState::Registered,
+            "Error" => PaymentState::Error,
+            "Refunded" => PaymentState::Refunded,
+            "Refunding" => PaymentState::Refunding,
+            "Waiting" => PaymentState::Waiting,
+            "Canceled" => PaymentState::Canceled,
+            "Contested" => PaymentState::Contested,
+            "WaitingBankValidation" => PaymentState::WaitingBankValidation,
+            "WaitingBankWithdraw" => PaymentState::WaitingBankWithdraw,
+            "Abandoned" => PaymentState::Abandoned,
+            _ => PaymentState::Other(state),
+        }
+    }
+}
+
+impl TryFrom<Name> for PaymentState {
+    type Error = String;
+
+    fn try_from(name: Name) -> std::result::Result<Self, String> {
+        match PaymentState::from(name.0) {
+            PaymentState::Other(state) if is_strict() => Err(unknown_value(&state)),
+            state => Ok(state),
+        }
+    }
+}
+
+impl From<PaymentState> for String {
+    fn from(state: PaymentState) -> Self {
+        match state {
+            PaymentState::Other(state) => state,
+            state => state.as_str().to_string(),
+        }
+    }
+}
+
+/// How a [Payment](crate::Payment) was made
+///
+/// The api adds means, an unknown means is kept as [Other](crate::PaymentMeans::Other).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Name", into = "String")]
+pub enum PaymentMeans {
+    Card,
+    Sepa,
+    Check,
+    Cash,
+    BankTransfer,
+    /// A means the client doesn't know, the `Other` means of the api is `Other("Other")`
+    Other(String),
+}
+
+impl PaymentMeans {
+    /// The name of the means in the api
+    pub fn as_str(&self) -> &str {
+        match self {
+            PaymentMeans::Card => "Card",
+            PaymentMeans::Sepa => "Sepa",
+            PaymentMeans::Check => "Check",
+            PaymentMeans::Cash => "Cash",
+            PaymentMeans::BankTransfer => "BankTransfer",
+            PaymentMeans::Other(means) => means,
+        }
+    }
+}
+
+impl From<String> for PaymentMeans {
+    fn from(means: String) -> Self {
+        match means.as_str() {
+            "Card" => PaymentMeans::Card,
+            "Sepa" => PaymentMeans::Sepa,
+            "Check" => PaymentMeans::Check,
+            "Cash" => PaymentMeans::Cash,
+            "BankTransfer" => PaymentMeans::BankTransfer,
+            _ => PaymentMeans::Other(means),
+        }
+    }
+}
+
+impl TryFrom<Name> for PaymentMeans {
+    type Error = String;
+
+    fn try_from(name: Name) -> std::result::Result<Self, String> {
+        match PaymentMeans::from(name.0) {
+            PaymentMeans::Other(means) if means != "Other" && is_strict() => {
+                Err(unknown_value(&means))
+            }
+            means => Ok(means),
+        }
+    }
+}
+
+impl From<PaymentMeans> for String {
+    fn from(means: PaymentMeans) -> Self {
+        match means {
+            PaymentMeans::Other(means) => means,
+            means => means.as_str().to_string(),
+        }
+    }
+}
+
+/// The transfer state of a [Payment](crate::Payment) to the organization
+///
+/// The api adds states, an unknown state is kept as [Other](crate::CashOutState::Other).
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Name", into = "String")]
+pub enum CashOutState {
+    /// The money is held by helloasso
+    MoneyIn,
+    /// The transfer to the organization is in progress
+    CashOutPending,
+    /// The money was transferred to the organization
+    CashedOut,
+    Refunding,
+    Refunded,
+    Contested,
+    Other(String),
+}
+
+impl CashOutState {
+    /// The name of the state in the api
+    pub fn as_str(&self) -> &str {
+        match self {
+            CashOutState::MoneyIn => "MoneyIn",
+            CashOutState::CashOutPending => "CashOutPending",
+            CashOutState::CashedOut => "CashedOut",
+            CashOutState::Refunding => "Refunding",
+            CashOutState::Refunded => "Refunded",
+            CashOutState::Contested => "Contested",
+            CashOutState::Other(state) => state,
+        }
+    }
+}
+
+impl From<String> for CashOutState {
+    fn from(state: String) -> Self {
+        match state.as_str() {
+            "MoneyIn" => CashOutState::MoneyIn,
+            "CashOutPending" => CashOutState::CashOutPending,
+            "CashedOut" => CashOutState::CashedOut,
+            "Refunding" => CashOutState::Refunding,
+            "Refunded" => CashOutState::Refunded,
+            "Contested" => CashOutState::Contested,
+            _ => CashOutState::Other(state),
+        }
+    }
+}
+
+impl TryFrom<Name> for CashOutState {
+    type Error = String;
+
+    fn try_from(name: Name) -> std::result::Result<Self, String> {
+        match CashOutState::from(name.0) {
+            CashOutState::Other(state) if is_strict() => Err(unknown_value(&state)),
+            state => Ok(state),
+        }
+    }
+}
+
+impl From<CashOutState> for String {
+    fn from(state: CashOutState) -> Self {
+        match state {
+            CashOutState::Other(state) => state,
+            state => state.as_str().to_string(),
+        }
+    }
+}
+
+/// The order a [Payment](crate::Payment) is attached to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentOrder {
+    pub id: u64,
+    pub form_slug: Option<String>,
+    pub form_type: Option<FormType>,
+    pub organization_slug: Option<String>,
+}
+
+/// Filters used when listing payments
+///
+/// Unset filters are not sent, the api default are used.
+///
+/// ```rust
+/// # use helloasso::{PaymentFilters, PaymentState};
+/// let filters = PaymentFilters::default()
+///     .with_state(PaymentState::Authorized)
+///     .with_user_search_key("jane@example.com");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaymentFilters {
+    /// Only the payments made after this date
+    pub from: Option<DateTime<Utc>>,
+    /// Only the payments made before this date
+    pub to: Option<DateTime<Utc>>,
+    /// Only the payments in these states, an [Other](crate::PaymentState::Other) state is sent by its name
+    pub states: Vec<PaymentState>,
+    /// Email, first name or last name of the payer
+    pub user_search_key: Option<String>,
+    /// Order of the payments by date
+    pub sort_order: Option<SortOrder>,
+}
+
+impl PaymentFilters {
+    pub fn with_from(mut self, from: DateTime<Utc>) -> Self {
+        self.from = Some(from);
+        self
+    }
+
+    pub fn with_to(mut self, to: DateTime<Utc>) -> Self {
+        self.to = Some(to);
+        self
+    }
+
+    pub fn with_state(mut self, state: PaymentState) -> Self {
+        self.states.push(state);
+        self
+    }
+
+    pub fn with_user_search_key(mut self, user_search_key: impl Into<String>) -> Self {
+        self.user_search_key = Some(user_search_key.into());
+        self
+    }
+
+    pub fn with_sort_order(mut self, sort_order: SortOrder) -> Self {
+        self.sort_order = Some(sort_order);
+        self
+    }
+
+    fn query(&self) -> Vec<(&str, String)> {
+        let mut query: Vec<_> = self
+            .states
+            .iter()
+            .map(|state| ("states", state.as_str().to_string()))
+            .collect();
+        if let Some(from) = self.from {
+            query.push(("from", from.to_rfc3339()));
+        }
+        if let Some(to) = self.to {
+            query.push(("to", to.to_rfc3339()));
+        }
+        if let Some(user_search_key) = &self.user_search_key {
+            query.push(("userSearchKey", user_search_key.clone()));
+        }
+        if let Some(sort_order) = self.sort_order {
+            query.push(("sortOrder", sort_order.as_str().to_string()));
+        }
+        query
+    }
+}
+
+/// Options of a refund, see [refund_payment](crate::HelloAsso::refund_payment)
+///
+/// The default options refund the whole payment and keep the order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RefundOptions {
+    /// Amount to refund, the whole payment when unset
+    pub amount: Option<Amount>,
+    /// Cancel the order of the payment, its future installments won't be collected
+    pub cancel_order: bool,
+    /// Send a mail to the payer about the refund
+    pub send_refund_mail: bool,
+    /// Reason of the refund, visible in the back office
+    pub comment: Option<String>,
+}
+
+impl RefundOptions {
+    fn query(&self) -> Vec<(&str, String)> {
+        let mut query = vec![
+            ("cancelOrder", self.cancel_order.to_string()),
+            ("sendRefundMail", self.send_refund_mail.to_string()),
+        ];
+        if let Some(amount) = self.amount {
+            query.push(("amount", amount.cents().to_string()));
+        }
+        if let Some(comment) = &self.comment {
+            query.push(("comment", comment.clone()));
+        }
+        query
+    }
+}
+
+/// A refund of a [Payment](crate::Payment)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefundOperation {
+    pub id: u64,
+    pub amount: Amount,
+    /// Part of the refunded amount that was a tip to helloasso
+    #[serde(default)]
+    pub amount_tip: Amount,
+    pub status: Option<String>,
+}
+
+impl HelloAsso {
+    /// Get a payment
+    pub async fn get_payment(&self, payment_id: u64) -> Result<Payment> {
+        let url = self.endpoint(&["payments", &payment_id.to_string()]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("payment", payment_id))
+    }
+
+    /// Same as `get_payment` but return the json sent by the api, see `get_order_raw`
+    pub async fn get_payment_raw(&self, payment_id: u64) -> Result<Value> {
+        let url = self.endpoint(&["payments", &payment_id.to_string()]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("payment", payment_id))
+    }
+
+    /// Same as `get_payment`, along with the request id and rate limit of the response
+    pub async fn get_payment_with_meta(&self, payment_id: u64) -> Result<(Payment, ResponseMeta)> {
+        let url = self.endpoint(&["payments", &payment_id.to_string()]);
+        self.get_with_meta(url, &())
+            .await
+            .map_err(|err| err.with_resource("payment", payment_id))
+    }
+
+    /// List the payments of an organization
+    pub async fn list_organization_payments(
+        &self,
+        organization_slug: &str,
+        filters: &PaymentFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Payment>> {
+        let url = self.endpoint(&["organizations", organization_slug, "payments"]);
+        let mut query = filters.query();
+        query.extend(pagination.query());
+
+        self.get(url, &query)
+            .await
+            .map_err(|err| err.with_resource("organization", organization_slug))
+    }
+
+    /// Stream every payment of an organization, the pages are fetched as the stream is consumed
+    pub fn stream_organization_payments<'a>(
+        &'a self,
+        organization_slug: &str,
+        filters: &PaymentFilters,
+    ) -> impl Stream<Item = Result<Payment>> + 'a {
+        let organization_slug = organization_slug.to_string();
+        let filters = filters.clone();
+
+        stream_pages(PaginationParams::page(1, PAGE_SIZE), move |params| {
+            let (organization_slug, filters) = (organization_slug.clone(), filters.clone());
+            async move {
+                self.list_organization_payments(&organization_slug, &filters, &params)
+                    .await
+            }
+        })
+    }
+
+    /// Get every payment of an organization, see `fetch_all_organization_orders`
+    pub async fn fetch_all_organization_payments(
+        &self,
+        organization_slug: &str,
+        filters: &PaymentFilters,
+        max_in_flight: usize,
+    ) -> Result<Vec<Payment>> {
+        fetch_all_concurrent(PAGE_SIZE, max_in_flight, |params| async move {
+            self.list_organization_payments(organization_slug, filters, &params)
+                .await
+        })
+        .await
+    }
+
+    /// List the payments of a form
+    pub async fn list_form_payments(
+        &self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+        filters: &PaymentFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Payment>> {
+        let url = self.endpoint(&[
+            "organizations",
+            organization_slug,
+            "forms",
+            form_type.as_str(),
+            form_slug,
+            "payments",
+        ]);
+        let mut query = filters.query();
+        query.extend(pagination.query());
+
+        self.get(url, &query)
+            .await
+            .map_err(|err| err.with_resource("form", form_slug))
+    }
+
+    /// Stream every payment of a form, see `stream_organization_payments`
+    pub fn stream_form_payments<'a>(
+        &'a self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+        filters: &PaymentFilters,
+    ) -> impl Stream<Item = Result<Payment>> + 'a {
+        let form = [organization_slug, form_slug].map(str::to_string);
+        let filters = filters.clone();
+
+        stream_pages(PaginationParams::page(1, PAGE_SIZE), move |params| {
+            let ([organization_slug, form_slug], filters) = (form.clone(), filters.clone());
+            let form_type = form_type.clone();
+            async move {
+                self.list_form_payments(
+                    &organization_slug,
+                    form_type,
+                    &form_slug,
+                    &filters,
+                    &params,
+                )
+                .await
+            }
+        })
+    }
+
+    /// Get every payment of a form, see `fetch_all_organization_orders`
+    pub async fn fetch_all_form_payments(
+        &self,
+        organization_slug: &str,
+        form_type: FormType,
+        form_slug: &str,
+        filters: &PaymentFilters,
+        max_in_flight: usize,
+    ) -> Result<Vec<Payment>> {
+        fetch_all_concurrent(PAGE_SIZE, max_in_flight, |params| {
+            let form_type = form_type.clone();
+            async move {
+                self.list_form_payments(organization_slug, form_type, form_slug, filters, &params)
+                    .await
+            }
+        })
+        .await
+    }
+
+    /// Refund a payment, fully or partially
+    ///
+    /// A partial refund can't exceed the amount of the payment that isn't refunded yet.
+    pub async fn refund_payment(
+        &self,
+        payment_id: u64,
+        options: &RefundOptions,
+    ) -> Result<RefundOperation> {
+        if options.amount == Some(Amount::ZERO) {
+            return Err(Error::InvalidInput(
+                "refund amount must be greater than zero".to_string(),
+            ));
+        }
+
+        // The options are passed in the query, the api expects an empty object as the body
+        let url = self.endpoint(&["payments", &payment_id.to_string(), "refund"]);
+        self.post(url, &options.query(), &serde_json::Map::new())
+            .await
+            .map_err(|err| err.with_resource("payment", payment_id))
+    }
+
+    /// Get a payment along with the order it references
+    ///
+    /// The order is `None` when the payment isn't attached to one.
+    pub async fn get_payment_with_order(
+        &self,
+        payment_id: u64,
+    ) -> Result<(Payment, Option<Order>)> {
+        let payment = self.get_payment(payment_id).await?;
+        let order = match &payment.order {
+            Some(order) => Some(self.get_order(order.id).await?),
+            None => None,
+        };
+
+        Ok((payment, order))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        test_util::{MockApi, PAYMENT_ID},
+        Amount, CashOutState, Error, HelloAsso, Payment, PaymentFilters, PaymentMeans,
+        PaymentState, RefundOperation, RefundOptions, SortOrder,
+    };
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+    use wiremock::{
+        matchers::{body_json, method, path, query_param},
+        Mock, ResponseTemplate,
+    };
+
+    #[test]
+    fn deserialize_payment() {
+        let payment: Payment = serde_json::from_value(json!({
+            "id": 42,
+            "amount": 1250,
+            "state": "Authorized",
+            "order": { "id": 12, "formSlug": "gala", "formType": "Event" }
+        }))
+        .unwrap();
+
+        assert_eq!(payment.amount, Amount::from_cents(1250));
+        assert_eq!(payment.state, Some(PaymentState::Authorized));
+        assert_eq!(payment.order.map(|order| order.id), Some(12));
+    }
+
+    #[test]
+    fn deserialize_payment_reconciliation() {
+        let payment: Payment = serde_json::from_value(json!({
+            "id": 42,
+            "amount": 1250,
+            "date": "2023-06-01T10:00:00+02:00",
+            "paymentMeans": "Card",
+            "cashOutState": "CashedOut",
+            "payer": { "email": "jane@example.com" }
+        }))
+        .unwrap();
+
+        assert_eq!(payment.payment_means, Some(PaymentMeans::Card));
+        assert_eq!(payment.cash_out_state, Some(CashOutState::CashedOut));
+        assert_eq!(
+            payment.payer.and_then(|payer| payer.email).as_deref(),
+            Some("jane@example.com")
+        );
+    }
+
+    #[test]
+    fn unknown_payment_values() {
+        let state: PaymentState = serde_json::from_str("\"WaitingBankWithdraw\"").unwrap();
+        assert_eq!(state, PaymentState::WaitingBankWithdraw);
+
+        let state: PaymentState = serde_json::from_str("\"NewState\"").unwrap();
+        assert_eq!(state, PaymentState::Other("NewState".to_string()));
+
+        let means: PaymentMeans = serde_json::from_str("\"NewMeans\"").unwrap();
+        assert_eq!(means, PaymentMeans::Other("NewMeans".to_string()));
+    }
+
+    #[test]
+    fn standalone_payment() {
+        let payment: Payment = serde_json::from_value(json!({ "id": 42, "amount": 1250 })).unwrap();
+
+        assert!(payment.order.is_none());
+    }
+
+    #[test]
+    fn payment_filters_query() {
+        assert!(PaymentFilters::default().query().is_empty());
+
+        let filters = PaymentFilters::default()
+            .with_from(Utc.with_ymd_and_hms(2023, 1, 1, 0, 0, 0).unwrap())
+            .with_state(PaymentState::Authorized)
+            .with_state(PaymentState::Refunded)
+            .with_user_search_key("jane@example.com")
+            .with_sort_order(SortOrder::Asc);
+        assert_eq!(
+            filters.query(),
+            vec![
+                ("states", "Authorized".to_string()),
+                ("states", "Refunded".to_string()),
+                ("from", "2023-01-01T00:00:00+00:00".to_string()),
+                ("userSearchKey", "jane@example.com".to_string()),
+                ("sortOrder", "Asc".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn payment_filters_other_state() {
+        let filters =
+            PaymentFilters::default().with_state(PaymentState::Other("NewState".to_string()));
+
+        assert_eq!(filters.query(), vec![("states", "NewState".to_string())]);
+    }
+
+    #[test]
+    fn refund_options_query() {
+        let options = RefundOptions {
+            amount: Some(Amount::from_cents(500)),
+            cancel_order: true,
+            comment: Some("Duplicate".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            options.query(),
+            vec![
+                ("cancelOrder", "true".to_string()),
+                ("sendRefundMail", "false".to_string()),
+                ("amount", "500".to_string()),
+                ("comment", "Duplicate".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn deserialize_refund_operation() {
+        let refund: RefundOperation =
+            serde_json::from_value(json!({ "id": 3, "amount": 500, "status": "Processed" }))
+                .unwrap();
+
+        assert_eq!(refund.amount, Amount::from_cents(500));
+        assert_eq!(refund.amount_tip, Amount::ZERO);
+    }
+
+    #[tokio::test]
+    async fn empty_refund() {
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string()).build();
+        let options = RefundOptions {
+            amount: Some(Amount::ZERO),
+            ..Default::default()
+        };
+
+        let refund = client.refund_payment(42, &options).await;
+
+        assert!(matches!(refund, Err(Error::InvalidInput(_))));
+    }
+
+    #[tokio::test]
+    async fn refund_body() {
+        let api = MockApi::start().await;
+        Mock::given(method("POST"))
+            .and(path(format!("/v5/payments/{PAYMENT_ID}/refund")))
+            .and(query_param("amount", "500"))
+            .and(body_json(json!({})))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "id": 3, "amount": 500, "status": "Processed" })),
+            )
+            .expect(1)
+            .mount(api.server())
+            .await;
+        let client = api.client().await.unwrap();
+        let options = RefundOptions {
+            amount: Some(Amount::from_cents(500)),
+            ..Default::default()
+        };
+
+        let refund = client.refund_payment(PAYMENT_ID, &options).await.unwrap();
+
+        assert_eq!(refund.amount, Amount::from_cents(500));
+    }
+}
@@ -0,0 +1,131 @@
+//! `rate_limit` the client side limit of the requests sent to the api
+
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::runtime::sleep;
+
+/// How many requests per second the client sends at most, a token bucket
+///
+/// Up to `burst` requests are sent at once, then `requests_per_second`. The limit
+/// is shared by the clones of the client and applies to every attempt of every
+/// request to the api, ie to keep a bulk export under the limits of the api.
+///
+/// ```rust
+/// # use helloasso::{HelloAsso, RateLimit};
+/// let builder = HelloAsso::builder("client_id".to_string(), "client_secret".to_string())
+///     .with_rate_limit(RateLimit::per_second(5));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub requests_per_second: u32,
+    /// Number of requests that can be sent at once after the client was idle
+    pub burst: u32,
+}
+
+impl RateLimit {
+    /// At most `requests` per second, with a burst of as many requests
+    pub fn per_second(requests: u32) -> Self {
+        RateLimit {
+            requests_per_second: requests,
+            burst: requests,
+        }
+    }
+
+    /// The delay between two requests
+    fn interval(&self) -> Duration {
+        Duration::from_secs(1) / self.requests_per_second.max(1)
+    }
+}
+
+/// The bucket of a [RateLimit], shared by the clones of the client
+#[derive(Debug, Clone)]
+pub(crate) struct RateLimiter {
+    limit: RateLimit,
+    /// When the bucket is full again, if no other request is sent
+    full_at: Arc<Mutex<Instant>>,
+}
+
+impl RateLimiter {
+    pub(crate) fn new(limit: RateLimit) -> Self {
+        RateLimiter {
+            limit,
+            full_at: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Wait until a request can be sent
+    pub(crate) async fn acquire(&self) {
+        let delay = self.reserve(Instant::now());
+        if !delay.is_zero() {
+            sleep(delay).await;
+        }
+    }
+
+    /// Take a token from the bucket, the delay is how long until it is available
+    fn reserve(&self, now: Instant) -> Duration {
+        let interval = self.limit.interval();
+        let burst = interval * self.limit.burst.saturating_sub(1);
+
+        let mut full_at = self
+            .full_at
+            .lock()
+            .expect("Rate limit lock is never poisoned");
+        let start = (*full_at).max(now);
+        *full_at = start + interval;
+
+        start.saturating_duration_since(now + burst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::RateLimiter;
+    use crate::RateLimit;
+
+    #[test]
+    fn burst_then_rate() {
+        let limiter = RateLimiter::new(RateLimit {
+            requests_per_second: 10,
+            burst: 2,
+        });
+        let now = Instant::now();
+
+        let delays: Vec<_> = (0..4).map(|_| limiter.reserve(now)).collect();
+
+        assert_eq!(
+            delays,
+            [
+                Duration::ZERO,
+                Duration::ZERO,
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+            ]
+        );
+        // The bucket fills again while idle
+        assert_eq!(
+            limiter.reserve(now + Duration::from_secs(1)),
+            Duration::ZERO
+        );
+    }
+
+    #[tokio::test]
+    async fn shared_by_clones() {
+        let limiter = RateLimiter::new(RateLimit {
+            requests_per_second: 20,
+            burst: 1,
+        });
+        let clone = limiter.clone();
+        let start = Instant::now();
+
+        limiter.acquire().await;
+        clone.acquire().await;
+        limiter.acquire().await;
+
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+}
@@ -0,0 +1,122 @@
+//! `retry` the retry policy applied to transient failures
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::token::jitter;
+
+/// How the requests failing with a transient error are retried
+///
+/// Connection errors, timeouts, `429 Too Many Requests` and `5xx` responses are
+/// retried. The delay doubles after each attempt, up to `max_delay`, and a random
+/// delay up to `jitter` is added. The default policy doesn't retry.
+///
+/// ```rust
+/// # use helloasso::{HelloAsso, RetryPolicy};
+/// let builder = HelloAsso::builder("client_id".to_string(), "client_secret".to_string())
+///     .with_retry_policy(RetryPolicy {
+///         max_attempts: 5,
+///         ..Default::default()
+///     });
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Longest delay of the backoff, before the jitter is added
+    ///
+    /// It also caps the `Retry-After` of a `429 Too Many Requests`: a longer delay
+    /// isn't waited for, the request fails with [RateLimited](crate::Error::RateLimited).
+    pub max_delay: Duration,
+    /// Maximum random delay added to each delay
+    pub jitter: Duration,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries
+    pub const NONE: RetryPolicy = RetryPolicy {
+        max_attempts: 1,
+        base_delay: Duration::from_millis(500),
+        max_delay: Duration::from_secs(30),
+        jitter: Duration::from_millis(100),
+    };
+
+    /// The delay before the retry following the attempt number `attempt`, starting at 1
+    pub(crate) fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_delay);
+
+        backoff + jitter(self.jitter)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::NONE
+    }
+}
+
+/// A retry about to happen, given to the `on_retry` callback of the builder
+///
+/// It can be used to count the retries in a metric.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryEvent {
+    /// The attempt that failed, starting at 1
+    pub attempt: u32,
+    pub max_attempts: u32,
+    /// Delay before the next attempt
+    pub delay: Duration,
+    /// Why the attempt failed, the status of the response or the connection error
+    pub cause: String,
+    /// The delay asked by the api with `Retry-After`
+    pub retry_after: Option<Duration>,
+}
+
+/// Callback called with each retry
+#[derive(Clone)]
+pub(crate) struct RetryHook(Arc<dyn Fn(&RetryEvent) + Send + Sync>);
+
+impl RetryHook {
+    pub(crate) fn new(hook: impl Fn(&RetryEvent) + Send + Sync + 'static) -> Self {
+        RetryHook(Arc::new(hook))
+    }
+
+    pub(crate) fn call(&self, event: &RetryEvent) {
+        (self.0)(event)
+    }
+}
+
+impl Debug for RetryHook {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("RetryHook")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RetryPolicy;
+    use std::time::Duration;
+
+    #[test]
+    fn exponential_delay() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            jitter: Duration::ZERO,
+        };
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+        assert_eq!(policy.delay(8), Duration::from_secs(1));
+        assert_eq!(policy.delay(u32::MAX), Duration::from_secs(1));
+    }
+}
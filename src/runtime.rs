@@ -0,0 +1,160 @@
+//! `runtime` the async primitives that depend on the executor
+//!
+//! The rest of the crate only uses those functions so it works with any executor.
+//! The `tokio` feature (default) and `async-std` feature use the timer of the
+//! matching runtime. The timer of async-std runs on its own thread and works under
+//! any executor, so it is preferred when both features are enabled: an async-std
+//! user only adding the `async-std` feature never needs a tokio reactor.
+//!
+//! Without any of them, or with the `tokio` feature outside of a tokio runtime, ie in
+//! the [blocking](crate::blocking) client, each sleep spawns an OS thread that wakes
+//! the task once the duration elapsed. It only happens on retries and rate limit
+//! waits, but enabling the feature of your runtime avoids it.
+
+use std::time::Duration;
+
+/// Wait for `duration` without blocking the executor
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(feature = "async-std")]
+    async_std::task::sleep(duration).await;
+
+    // The timer of tokio panics outside of a tokio runtime
+    #[cfg(all(feature = "tokio", not(feature = "async-std")))]
+    if tokio::runtime::Handle::try_current().is_ok() {
+        tokio::time::sleep(duration).await;
+    } else {
+        thread_sleep::ThreadSleep::new(duration).await;
+    }
+
+    #[cfg(not(any(feature = "tokio", feature = "async-std")))]
+    thread_sleep::ThreadSleep::new(duration).await;
+}
+
+/// Run a future to completion on the current thread, parking it while the future is pending
+#[cfg(feature = "blocking")]
+pub(crate) fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    use std::{
+        pin::pin,
+        sync::Arc,
+        task::{Context, Poll, Wake, Waker},
+        thread::{self, Thread},
+    };
+
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+#[cfg_attr(feature = "async-std", allow(dead_code))]
+mod thread_sleep {
+    use std::{
+        future::Future,
+        pin::Pin,
+        sync::{Arc, Mutex},
+        task::{Context, Poll, Waker},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    /// A future that is woken by a thread once the deadline is reached
+    ///
+    /// A thread is spawned the first time the future is polled, for every sleep.
+    pub(crate) struct ThreadSleep {
+        deadline: Instant,
+        waker: Option<Arc<Mutex<Waker>>>,
+    }
+
+    impl ThreadSleep {
+        pub(crate) fn new(duration: Duration) -> Self {
+            ThreadSleep {
+                deadline: Instant::now() + duration,
+                waker: None,
+            }
+        }
+    }
+
+    impl Future for ThreadSleep {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if Instant::now() >= self.deadline {
+                return Poll::Ready(());
+            }
+
+            match &self.waker {
+                Some(waker) => {
+                    *waker.lock().expect("Sleep waker is never poisoned") = cx.waker().clone();
+                }
+                None => {
+                    let waker = Arc::new(Mutex::new(cx.waker().clone()));
+                    let thread_waker = waker.clone();
+                    let deadline = self.deadline;
+                    thread::spawn(move || {
+                        thread::sleep(deadline.saturating_duration_since(Instant::now()));
+                        thread_waker
+                            .lock()
+                            .expect("Sleep waker is never poisoned")
+                            .wake_by_ref();
+                    });
+                    self.waker = Some(waker);
+                }
+            }
+
+            Poll::Pending
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sleep, thread_sleep::ThreadSleep};
+    use std::time::{Duration, Instant};
+
+    #[tokio::test]
+    async fn sleep_waits() {
+        let start = Instant::now();
+        sleep(Duration::from_millis(20)).await;
+
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[cfg(feature = "async-std")]
+    #[async_std::test]
+    async fn sleep_waits_async_std() {
+        let start = Instant::now();
+        sleep(Duration::from_millis(20)).await;
+
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[cfg(feature = "blocking")]
+    #[test]
+    fn sleep_outside_of_a_runtime() {
+        let start = Instant::now();
+        super::block_on(sleep(Duration::from_millis(20)));
+
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+
+    #[tokio::test]
+    async fn thread_sleep_waits() {
+        let start = Instant::now();
+        ThreadSleep::new(Duration::from_millis(20)).await;
+
+        assert!(start.elapsed() >= Duration::from_millis(20));
+    }
+}
@@ -0,0 +1,341 @@
+//! `scoped` clients bound to an organization or a form, so their slugs aren't repeated on every call
+
+use futures_util::Stream;
+
+use crate::{
+    CheckoutIntent, CheckoutIntentRequest, FormLightModel, FormPublic, FormType, FormsFilters,
+    HelloAsso, Item, ItemFilters, Order, OrderFilters, Organization, OrganizationLegalInfo,
+    OrganizationProfile, Paginated, PaginationParams, Participant, Payment, PaymentFilters,
+    QuickCreateForm, QuickCreatedForm, Result,
+};
+
+/// A client bound to an organization, created with [organization](crate::HelloAsso::organization)
+///
+/// Each method is the method of the [client](crate::HelloAsso) of the same resource with the slug filled in.
+///
+/// ```rust,no_run
+/// # use helloasso::{HelloAsso, OrderFilters, PaginationParams};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> helloasso::Result<()> {
+/// # let client = HelloAsso::new("client_id".to_string(), "client_secret".to_string()).await?;
+/// let organization = client.organization("my-asso");
+/// let orders = organization
+///     .orders(&OrderFilters::default(), &PaginationParams::default())
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct OrganizationClient<'a> {
+    client: &'a HelloAsso,
+    slug: String,
+}
+
+impl HelloAsso {
+    /// A client bound to the organization `slug`
+    pub fn organization(&self, slug: impl Into<String>) -> OrganizationClient<'_> {
+        OrganizationClient {
+            client: self,
+            slug: slug.into(),
+        }
+    }
+}
+
+impl<'a> OrganizationClient<'a> {
+    pub fn slug(&self) -> &str {
+        &self.slug
+    }
+
+    /// A client bound to a form of this organization
+    pub fn form(&self, form_type: FormType, form_slug: impl Into<String>) -> FormClient<'a> {
+        FormClient {
+            client: self.client,
+            organization_slug: self.slug.clone(),
+            form_type,
+            form_slug: form_slug.into(),
+        }
+    }
+
+    /// See [get_organization](crate::HelloAsso::get_organization)
+    pub async fn get(&self) -> Result<Organization> {
+        self.client.get_organization(&self.slug).await
+    }
+
+    /// See [get_organization_legal_info](crate::HelloAsso::get_organization_legal_info)
+    pub async fn legal_info(&self) -> Result<OrganizationLegalInfo> {
+        self.client.get_organization_legal_info(&self.slug).await
+    }
+
+    /// See [get_organization_public_profile](crate::HelloAsso::get_organization_public_profile)
+    pub async fn public_profile(&self) -> Result<OrganizationProfile> {
+        self.client
+            .get_organization_public_profile(&self.slug)
+            .await
+    }
+
+    /// See [list_organization_orders](crate::HelloAsso::list_organization_orders)
+    pub async fn orders(
+        &self,
+        filters: &OrderFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Order>> {
+        self.client
+            .list_organization_orders(&self.slug, filters, pagination)
+            .await
+    }
+
+    /// See [stream_organization_orders](crate::HelloAsso::stream_organization_orders)
+    pub fn stream_orders(&self, filters: &OrderFilters) -> impl Stream<Item = Result<Order>> + 'a {
+        self.client.stream_organization_orders(&self.slug, filters)
+    }
+
+    /// See [list_organization_payments](crate::HelloAsso::list_organization_payments)
+    pub async fn payments(
+        &self,
+        filters: &PaymentFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Payment>> {
+        self.client
+            .list_organization_payments(&self.slug, filters, pagination)
+            .await
+    }
+
+    /// See [stream_organization_payments](crate::HelloAsso::stream_organization_payments)
+    pub fn stream_payments(
+        &self,
+        filters: &PaymentFilters,
+    ) -> impl Stream<Item = Result<Payment>> + 'a {
+        self.client
+            .stream_organization_payments(&self.slug, filters)
+    }
+
+    /// See [list_forms](crate::HelloAsso::list_forms)
+    pub async fn forms(
+        &self,
+        filters: &FormsFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<FormLightModel>> {
+        self.client
+            .list_forms(&self.slug, filters, pagination)
+            .await
+    }
+
+    /// See [stream_forms](crate::HelloAsso::stream_forms)
+    pub fn stream_forms(
+        &self,
+        filters: &FormsFilters,
+    ) -> impl Stream<Item = Result<FormLightModel>> + 'a {
+        self.client.stream_forms(&self.slug, filters)
+    }
+
+    /// See [list_organization_items](crate::HelloAsso::list_organization_items)
+    pub async fn items(
+        &self,
+        filters: &ItemFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Item>> {
+        self.client
+            .list_organization_items(&self.slug, filters, pagination)
+            .await
+    }
+
+    /// See [stream_organization_items](crate::HelloAsso::stream_organization_items)
+    pub fn stream_items(&self, filters: &ItemFilters) -> impl Stream<Item = Result<Item>> + 'a {
+        self.client.stream_organization_items(&self.slug, filters)
+    }
+
+    /// See [create_checkout_intent](crate::HelloAsso::create_checkout_intent)
+    pub async fn create_checkout_intent(
+        &self,
+        request: &CheckoutIntentRequest,
+    ) -> Result<CheckoutIntent> {
+        self.client
+            .create_checkout_intent(&self.slug, request)
+            .await
+    }
+
+    /// See [get_checkout_intent](crate::HelloAsso::get_checkout_intent)
+    pub async fn get_checkout_intent(&self, checkout_intent_id: u64) -> Result<CheckoutIntent> {
+        self.client
+            .get_checkout_intent(&self.slug, checkout_intent_id)
+            .await
+    }
+
+    /// See [quick_create_form](crate::HelloAsso::quick_create_form)
+    pub async fn quick_create_form(
+        &self,
+        form_type: FormType,
+        form: &QuickCreateForm,
+    ) -> Result<QuickCreatedForm> {
+        self.client
+            .quick_create_form(&self.slug, form_type, form)
+            .await
+    }
+}
+
+/// A client bound to a form, created with [form](crate::OrganizationClient::form)
+///
+/// ```rust,no_run
+/// # use helloasso::{FormType, HelloAsso, ItemFilters, PaginationParams};
+/// # #[tokio::main(flavor = "current_thread")]
+/// # async fn main() -> helloasso::Result<()> {
+/// # let client = HelloAsso::new("client_id".to_string(), "client_secret".to_string()).await?;
+/// let gala = client.organization("my-asso").form(FormType::Event, "gala");
+/// let attendees = gala
+///     .items(&ItemFilters::default(), &PaginationParams::default())
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct FormClient<'a> {
+    client: &'a HelloAsso,
+    organization_slug: String,
+    form_type: FormType,
+    form_slug: String,
+}
+
+impl<'a> FormClient<'a> {
+    pub fn organization_slug(&self) -> &str {
+        &self.organization_slug
+    }
+
+    pub fn form_type(&self) -> &FormType {
+        &self.form_type
+    }
+
+    pub fn form_slug(&self) -> &str {
+        &self.form_slug
+    }
+
+    /// See [get_form](crate::HelloAsso::get_form)
+    pub async fn public_data(&self) -> Result<FormPublic> {
+        self.client
+            .get_form(
+                &self.organization_slug,
+                self.form_type.clone(),
+                &self.form_slug,
+            )
+            .await
+    }
+
+    /// See [get_form_participants](crate::HelloAsso::get_form_participants)
+    pub async fn participants(&self) -> Result<Vec<Participant>> {
+        self.client
+            .get_form_participants(
+                &self.organization_slug,
+                self.form_type.clone(),
+                &self.form_slug,
+            )
+            .await
+    }
+
+    /// See [list_form_orders](crate::HelloAsso::list_form_orders)
+    pub async fn orders(
+        &self,
+        filters: &OrderFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Order>> {
+        self.client
+            .list_form_orders(
+                &self.organization_slug,
+                self.form_type.clone(),
+                &self.form_slug,
+                filters,
+                pagination,
+            )
+            .await
+    }
+
+    /// See [stream_form_orders](crate::HelloAsso::stream_form_orders)
+    pub fn stream_orders(&self, filters: &OrderFilters) -> impl Stream<Item = Result<Order>> + 'a {
+        self.client.stream_form_orders(
+            &self.organization_slug,
+            self.form_type.clone(),
+            &self.form_slug,
+            filters,
+        )
+    }
+
+    /// See [list_form_payments](crate::HelloAsso::list_form_payments)
+    pub async fn payments(
+        &self,
+        filters: &PaymentFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Payment>> {
+        self.client
+            .list_form_payments(
+                &self.organization_slug,
+                self.form_type.clone(),
+                &self.form_slug,
+                filters,
+                pagination,
+            )
+            .await
+    }
+
+    /// See [stream_form_payments](crate::HelloAsso::stream_form_payments)
+    pub fn stream_payments(
+        &self,
+        filters: &PaymentFilters,
+    ) -> impl Stream<Item = Result<Payment>> + 'a {
+        self.client.stream_form_payments(
+            &self.organization_slug,
+            self.form_type.clone(),
+            &self.form_slug,
+            filters,
+        )
+    }
+
+    /// See [list_form_items](crate::HelloAsso::list_form_items)
+    pub async fn items(
+        &self,
+        filters: &ItemFilters,
+        pagination: &PaginationParams,
+    ) -> Result<Paginated<Item>> {
+        self.client
+            .list_form_items(
+                &self.organization_slug,
+                self.form_type.clone(),
+                &self.form_slug,
+                filters,
+                pagination,
+            )
+            .await
+    }
+
+    /// See [stream_form_items](crate::HelloAsso::stream_form_items)
+    pub fn stream_items(&self, filters: &ItemFilters) -> impl Stream<Item = Result<Item>> + 'a {
+        self.client.stream_form_items(
+            &self.organization_slug,
+            self.form_type.clone(),
+            &self.form_slug,
+            filters,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{FormType, HelloAsso};
+
+    #[test]
+    fn organization_client() {
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string()).build();
+
+        let organization = client.organization("my-asso");
+
+        assert_eq!(organization.slug(), "my-asso");
+    }
+
+    #[test]
+    fn form_client() {
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string()).build();
+
+        let form = client.organization("my-asso").form(FormType::Event, "gala");
+
+        assert_eq!(form.organization_slug(), "my-asso");
+        assert_eq!(form.form_type(), &FormType::Event);
+        assert_eq!(form.form_slug(), "gala");
+    }
+}
@@ -0,0 +1,51 @@
+//! `tags` the public tags set by the organizations on their forms
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Amount, HelloAsso, Result};
+
+/// The usage of a public tag
+///
+/// The forms using a tag can be found with [DirectoryFormsQuery::with_tag](crate::DirectoryFormsQuery::with_tag).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagStatistics {
+    pub name: String,
+    #[serde(default)]
+    pub form_count: u64,
+    #[serde(default)]
+    pub organization_count: u64,
+    /// Amount collected by the forms using the tag
+    #[serde(default)]
+    pub amount_collected: Amount,
+}
+
+impl HelloAsso {
+    /// Get the usage of a public tag
+    pub async fn get_tag(&self, tag_name: &str) -> Result<TagStatistics> {
+        let url = self.endpoint(&["tags", tag_name]);
+        self.get(url, &())
+            .await
+            .map_err(|err| err.with_resource("tag", tag_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Amount, TagStatistics};
+    use serde_json::json;
+
+    #[test]
+    fn deserialize_tag() {
+        let tag: TagStatistics = serde_json::from_value(json!({
+            "name": "solidarite",
+            "formCount": 12,
+            "organizationCount": 5,
+            "amountCollected": 125000
+        }))
+        .unwrap();
+
+        assert_eq!(tag.form_count, 12);
+        assert_eq!(tag.amount_collected, Amount::from_cents(125000));
+    }
+}
@@ -0,0 +1,312 @@
+//! `test_util` a mock of the helloasso api to test the code using this crate
+//!
+//! Enabled by the `test-util` feature. [MockApi] starts a local server answering
+//! realistic token, organization, order and payment responses, the client returned
+//! by [MockApi::client] is already authenticated against it.
+//!
+//! ```rust,no_run
+//! use helloasso::test_util::{MockApi, ORGANIZATION_SLUG, PAYMENT_ID};
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> helloasso::Result<()> {
+//! let api = MockApi::start().await;
+//! let client = api.client().await?;
+//!
+//! let organization = client.get_organization(ORGANIZATION_SLUG).await?;
+//! let payment = client.get_payment(PAYMENT_ID).await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Other responses can be mounted on [MockApi::server], a mock with a priority
+//! lower than 5 answers instead of the fixtures.
+
+use serde_json::{json, Value};
+use wiremock::{
+    matchers::{body_string_contains, header, method, path},
+    Mock, MockServer, Request, ResponseTemplate,
+};
+
+use crate::{HelloAsso, Result};
+
+pub const CLIENT_ID: &str = "mock-client-id";
+pub const CLIENT_SECRET: &str = "mock-client-secret";
+/// The access token answered by the token endpoint, required by every api endpoint
+pub const ACCESS_TOKEN: &str = "mock-access-token";
+pub const REFRESH_TOKEN: &str = "mock-refresh-token";
+pub const ORGANIZATION_SLUG: &str = "mock-asso";
+pub const FORM_SLUG: &str = "adhesion-2024";
+pub const ORDER_ID: u64 = 12578;
+pub const PAYMENT_ID: u64 = 24763;
+
+/// A mock of the helloasso api, stopped when dropped
+///
+/// The token endpoint only accepts [CLIENT_ID] and [CLIENT_SECRET], or [REFRESH_TOKEN].
+/// The api endpoints answer `401 Unauthorized` to any other token than [ACCESS_TOKEN].
+#[derive(Debug)]
+pub struct MockApi {
+    server: MockServer,
+}
+
+impl MockApi {
+    /// Start a server answering the fixtures of this module
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/oauth2/token"))
+            .and(body_string_contains("grant_type=client_credentials"))
+            .and(body_string_contains(format!("client_id={CLIENT_ID}")))
+            .and(body_string_contains(format!(
+                "client_secret={CLIENT_SECRET}"
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(token()))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/oauth2/token"))
+            .and(body_string_contains("grant_type=refresh_token"))
+            .and(body_string_contains(format!(
+                "refresh_token={REFRESH_TOKEN}"
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(token()))
+            .mount(&server)
+            .await;
+        // Any other credentials are refused
+        Mock::given(method("POST"))
+            .and(path("/oauth2/token"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(authentication_error()))
+            .with_priority(10)
+            .mount(&server)
+            .await;
+
+        let endpoints = [
+            (
+                format!("/v5/organizations/{ORGANIZATION_SLUG}"),
+                organization(),
+            ),
+            (
+                format!("/v5/organizations/{ORGANIZATION_SLUG}/orders"),
+                page(vec![order()]),
+            ),
+            (format!("/v5/orders/{ORDER_ID}"), order()),
+            (
+                format!("/v5/organizations/{ORGANIZATION_SLUG}/payments"),
+                page(vec![payment()]),
+            ),
+            (format!("/v5/payments/{PAYMENT_ID}"), payment()),
+        ];
+        for (endpoint, body) in endpoints {
+            Mock::given(method("GET"))
+                .and(path(endpoint))
+                .and(header("authorization", format!("Bearer {ACCESS_TOKEN}")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(body))
+                .mount(&server)
+                .await;
+        }
+        // Any other access token is refused
+        Mock::given(|request: &Request| {
+            let authorization = request.headers.get("authorization");
+            request.url.path().starts_with("/v5/")
+                && authorization.and_then(|value| value.to_str().ok())
+                    != Some(&format!("Bearer {ACCESS_TOKEN}"))
+        })
+        .respond_with(ResponseTemplate::new(401).set_body_json(unauthorized()))
+        .with_priority(10)
+        .mount(&server)
+        .await;
+
+        MockApi { server }
+    }
+
+    /// The url of the server, to use with the `base_url` method of the builder
+    pub fn uri(&self) -> String {
+        self.server.uri()
+    }
+
+    /// The underlying server, to mount other responses or check the received requests
+    pub fn server(&self) -> &MockServer {
+        &self.server
+    }
+
+    /// A client authenticated against this server
+    pub async fn client(&self) -> Result<HelloAsso> {
+        let client = HelloAsso::builder(CLIENT_ID.to_string(), CLIENT_SECRET.to_string())
+            .with_base_url(&self.uri())?
+            .get_token()
+            .await?
+            .config_client()?
+            .build();
+
+        Ok(client)
+    }
+}
+
+/// The response of the token endpoint, for both the client credentials and the refresh
+pub fn token() -> Value {
+    json!({
+        "access_token": ACCESS_TOKEN,
+        "token_type": "bearer",
+        "expires_in": 1799,
+        "refresh_token": REFRESH_TOKEN,
+    })
+}
+
+/// The response of the token endpoint to unknown credentials
+pub fn authentication_error() -> Value {
+    json!({
+        "error": "unauthorized_client",
+        "error_description": "client_id or client_secret is invalid",
+    })
+}
+
+/// The response of the api to a request without a valid access token
+pub fn unauthorized() -> Value {
+    json!({ "message": "Authorization has been denied for this request." })
+}
+
+pub fn organization() -> Value {
+    json!({
+        "organizationSlug": ORGANIZATION_SLUG,
+        "name": "Association Mock",
+        "description": "Une association de test",
+        "city": "Bordeaux",
+        "zipCode": "33000",
+        "address": "12 rue Sainte-Catherine",
+        "logo": "https://cdn.helloasso.com/img/logos/mock-asso.png",
+        "url": "https://www.helloasso.com/associations/mock-asso",
+        "type": "Association1901",
+        "category": "Sport",
+        "rnaNumber": "W332000000",
+        "fiscalReceiptEligibility": true,
+        "fiscalReceiptIssuanceEnabled": false,
+        "isAuthenticated": true,
+    })
+}
+
+fn payer() -> Value {
+    json!({
+        "email": "jeanne.martin@example.com",
+        "firstName": "Jeanne",
+        "lastName": "Martin",
+        "address": "3 place de la Bourse",
+        "city": "Bordeaux",
+        "zipCode": "33000",
+        "country": "FRA",
+    })
+}
+
+pub fn order() -> Value {
+    json!({
+        "id": ORDER_ID,
+        "date": "2024-03-02T14:21:08.51+01:00",
+        "formSlug": FORM_SLUG,
+        "formType": "Membership",
+        "organizationSlug": ORGANIZATION_SLUG,
+        "amount": { "total": 3000, "vat": 0, "discount": 0 },
+        "payer": payer(),
+        "items": [{
+            "id": 31544,
+            "name": "Adhésion annuelle",
+            "amount": 3000,
+            "tierId": 8871,
+            "type": "Membership",
+            "isEligibleTaxReceipt": true,
+            "user": { "firstName": "Jeanne", "lastName": "Martin" },
+            "customFields": [],
+            "options": [],
+        }],
+        "payments": [{
+            "id": PAYMENT_ID,
+            "amount": 3000,
+            "state": "Authorized",
+            "date": "2024-03-02T14:21:08.51+01:00",
+        }],
+    })
+}
+
+pub fn payment() -> Value {
+    json!({
+        "id": PAYMENT_ID,
+        "amount": 3000,
+        "date": "2024-03-02T14:21:08.51+01:00",
+        "state": "Authorized",
+        "paymentMeans": "Card",
+        "cashOutState": "MoneyIn",
+        "payer": payer(),
+        "order": {
+            "id": ORDER_ID,
+            "formSlug": FORM_SLUG,
+            "formType": "Membership",
+            "organizationSlug": ORGANIZATION_SLUG,
+        },
+    })
+}
+
+/// A single page holding `data`, as answered by the list endpoints
+pub fn page(data: Vec<Value>) -> Value {
+    json!({
+        "data": data,
+        "pagination": {
+            "pageIndex": 1,
+            "pageSize": 20,
+            "totalPages": 1,
+            "totalCount": data.len(),
+            "continuationToken": "",
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MockApi, ORDER_ID, ORGANIZATION_SLUG, PAYMENT_ID};
+    use crate::{OrderFilters, PaginationParams, PaymentFilters, PaymentState};
+
+    #[tokio::test]
+    async fn fixtures_are_decoded() {
+        let api = MockApi::start().await;
+        let client = api.client().await.unwrap();
+
+        let organization = client.get_organization(ORGANIZATION_SLUG).await.unwrap();
+        assert_eq!(organization.organization_slug, ORGANIZATION_SLUG);
+
+        let order = client.get_order(ORDER_ID).await.unwrap();
+        assert_eq!(order.payments[0].id, PAYMENT_ID);
+
+        let payment = client.get_payment(PAYMENT_ID).await.unwrap();
+        assert_eq!(payment.state, Some(PaymentState::Authorized));
+
+        let (payment_with_meta, meta) = client.get_payment_with_meta(PAYMENT_ID).await.unwrap();
+        assert_eq!(payment_with_meta, payment);
+        assert_eq!(meta.status, 200);
+
+        let raw_payment = client.get_payment_raw(PAYMENT_ID).await.unwrap();
+        assert_eq!(raw_payment["payer"]["city"], "Bordeaux");
+
+        let orders = client
+            .list_organization_orders(
+                ORGANIZATION_SLUG,
+                &OrderFilters::default(),
+                &PaginationParams::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(orders.data, vec![order]);
+
+        let payments = client
+            .list_organization_payments(
+                ORGANIZATION_SLUG,
+                &PaymentFilters::default(),
+                &PaginationParams::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(payments.data, vec![payment.clone()]);
+
+        let all_payments = client
+            .fetch_all_organization_payments(ORGANIZATION_SLUG, &PaymentFilters::default(), 4)
+            .await
+            .unwrap();
+        assert_eq!(all_payments, vec![payment]);
+    }
+}
@@ -0,0 +1,282 @@
+//! `token` the oauth2 tokens of the client
+
+use std::{
+    fmt::{self, Debug, Formatter},
+    ops::Deref,
+    sync::Arc,
+    time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// The tokens of a client, as they should be persisted
+///
+/// The tokens are redacted from the debug output. They are kept in clear to be
+/// persisted, with the `secrecy` feature they are zeroized on drop.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredToken {
+    pub access_token: TokenValue,
+    pub refresh_token: TokenValue,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A token of a [StoredToken], redacted from the debug output
+///
+/// The value is serialized in clear. With the `secrecy` feature it is zeroized on drop.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TokenValue(String);
+
+impl TokenValue {
+    pub fn new(value: impl Into<String>) -> Self {
+        TokenValue(value.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Take the value, the token left behind is empty
+    pub fn into_string(mut self) -> String {
+        std::mem::take(&mut self.0)
+    }
+}
+
+impl Deref for TokenValue {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl From<String> for TokenValue {
+    fn from(value: String) -> Self {
+        TokenValue(value)
+    }
+}
+
+impl From<&str> for TokenValue {
+    fn from(value: &str) -> Self {
+        TokenValue(value.to_string())
+    }
+}
+
+impl PartialEq<str> for TokenValue {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for TokenValue {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl Debug for TokenValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        redacted(self, f)
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl Drop for TokenValue {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(&mut self.0);
+    }
+}
+
+/// A session of a client, saved to be restored later with [from_session](crate::HelloAsso::from_session)
+///
+/// The urls are those of the environment of the client, a session saved without them
+/// is restored on the default environment.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    #[serde(flatten)]
+    pub token: StoredToken,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<Url>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_url: Option<Url>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authorize_url: Option<Url>,
+}
+
+/// The tokens of a client and when to refresh them
+#[derive(Debug, Clone)]
+pub(crate) struct TokenState {
+    pub access_token: Secret,
+    pub refresh_token: Secret,
+    pub expires_at: DateTime<Utc>,
+    /// How long before the expiration of the token it should be refreshed,
+    /// the refresh margin plus a random jitter
+    pub refresh_before: Duration,
+}
+
+impl TokenState {
+    pub fn new(token: StoredToken, refresh_before: Duration) -> Self {
+        TokenState {
+            access_token: token.access_token.into_string().into(),
+            refresh_token: token.refresh_token.into_string().into(),
+            expires_at: token.expires_at,
+            refresh_before,
+        }
+    }
+
+    /// Whether both hold the same tokens, without exposing them
+    pub fn same_tokens(&self, other: &TokenState) -> bool {
+        self.access_token == other.access_token
+            && self.refresh_token == other.refresh_token
+            && self.expires_at == other.expires_at
+    }
+
+    /// The tokens with their values exposed, to be persisted
+    pub fn stored(&self) -> StoredToken {
+        StoredToken {
+            access_token: self.access_token.expose_secret().into(),
+            refresh_token: self.refresh_token.expose_secret().into(),
+            expires_at: self.expires_at,
+        }
+    }
+}
+
+#[cfg(feature = "secrecy")]
+type SecretValue = secrecy::SecretString;
+#[cfg(not(feature = "secrecy"))]
+type SecretValue = String;
+
+/// A secret, ie the client secret or a token, redacted from the debug output
+///
+/// With the `secrecy` feature the value is a [SecretString](secrecy::SecretString),
+/// zeroized on drop. It is only exposed to build the requests.
+#[derive(Clone)]
+pub(crate) struct Secret(SecretValue);
+
+impl Secret {
+    pub fn expose_secret(&self) -> &str {
+        #[cfg(feature = "secrecy")]
+        return secrecy::ExposeSecret::expose_secret(&self.0);
+        #[cfg(not(feature = "secrecy"))]
+        return &self.0;
+    }
+}
+
+impl From<String> for Secret {
+    // The conversion is needed with the `secrecy` feature
+    #[allow(clippy::useless_conversion)]
+    fn from(value: String) -> Self {
+        Secret(value.into())
+    }
+}
+
+impl PartialEq for Secret {
+    fn eq(&self, other: &Self) -> bool {
+        self.expose_secret() == other.expose_secret()
+    }
+}
+
+impl Debug for Secret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        redacted(self, f)
+    }
+}
+
+impl<'de> Deserialize<'de> for Secret {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Secret::from)
+    }
+}
+
+/// Debug a secret, ie a token, without its value
+pub(crate) fn redacted<T>(_: &T, f: &mut Formatter) -> fmt::Result {
+    f.write_str("\"***\"")
+}
+
+/// How long before its expiration an access token is refreshed by default
+pub(crate) const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// A random duration between zero and `max`
+///
+/// Used to spread the refresh of the tokens of clients started at the same time.
+pub(crate) fn jitter(max: Duration) -> Duration {
+    if max.is_zero() {
+        return Duration::ZERO;
+    }
+
+    let mut bytes = [0; 8];
+    getrandom::fill(&mut bytes).expect("The random generator of the system is available");
+    let random = u64::from_ne_bytes(bytes);
+    Duration::from_nanos(random % max.as_nanos().min(u64::MAX as u128) as u64)
+}
+
+/// Callback called with the new tokens each time they are fetched or refreshed
+#[derive(Clone)]
+pub(crate) struct TokenRotatedHook(Arc<dyn Fn(&StoredToken) + Send + Sync>);
+
+impl TokenRotatedHook {
+    pub(crate) fn new(hook: impl Fn(&StoredToken) + Send + Sync + 'static) -> Self {
+        TokenRotatedHook(Arc::new(hook))
+    }
+
+    pub(crate) fn call(&self, token: &StoredToken) {
+        (self.0)(token)
+    }
+}
+
+impl Debug for TokenRotatedHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("TokenRotatedHook")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{jitter, TokenState};
+    use crate::StoredToken;
+    use chrono::Utc;
+    use std::time::Duration;
+
+    #[test]
+    fn jitter_within_bounds() {
+        let max = Duration::from_secs(30);
+
+        for _ in 0..100 {
+            assert!(jitter(max) < max);
+        }
+        assert_eq!(jitter(Duration::ZERO), Duration::ZERO);
+    }
+
+    #[test]
+    fn same_tokens() {
+        let token = StoredToken {
+            access_token: "access".into(),
+            refresh_token: "refresh".into(),
+            expires_at: Utc::now(),
+        };
+        let state = TokenState::new(token.clone(), Duration::ZERO);
+
+        assert!(state.same_tokens(&TokenState::new(token.clone(), Duration::from_secs(60))));
+        let mut rotated = token;
+        rotated.refresh_token = "rotated".into();
+        assert!(!state.same_tokens(&TokenState::new(rotated, Duration::ZERO)));
+    }
+
+    #[test]
+    fn move_out_token_fields() {
+        let token = StoredToken {
+            access_token: "access".into(),
+            refresh_token: "refresh".into(),
+            expires_at: Utc::now(),
+        };
+
+        // Compiles whatever the features, `StoredToken` doesn't implement `Drop`
+        let StoredToken { access_token, .. } = token;
+
+        assert_eq!(access_token, "access");
+        assert_eq!(format!("{access_token:?}"), "\"***\"");
+        assert_eq!(access_token.into_string(), "access");
+    }
+}
@@ -0,0 +1,187 @@
+//! `transport` how the http requests of the client are sent
+
+use std::{fmt::Debug, sync::Arc};
+
+use futures_util::future::BoxFuture;
+use reqwest::{Request, Response};
+
+#[cfg(feature = "reqwest-middleware")]
+use crate::Error;
+use crate::Result;
+
+/// Send the http requests of the client, both to the api and to fetch the tokens
+///
+/// The requests are sent with reqwest by default. Another transport can be set on the
+/// [builder](crate::HelloAsso::builder) with `transport`, ie to answer canned responses
+/// in tests. A [Response](reqwest::Response) can be created from an `http::Response`.
+/// With the `reqwest-middleware` feature, a `ClientWithMiddleware` is a transport.
+pub trait HttpTransport: Debug + Send + Sync {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>>;
+}
+
+impl HttpTransport for reqwest::Client {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+        Box::pin(async move { Ok(reqwest::Client::execute(self, request).await?) })
+    }
+}
+
+/// The middlewares of the client, ie retries or tracing, apply to the requests of the api
+#[cfg(feature = "reqwest-middleware")]
+impl HttpTransport for reqwest_middleware::ClientWithMiddleware {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+        Box::pin(async move {
+            reqwest_middleware::ClientWithMiddleware::execute(self, request)
+                .await
+                .map_err(|err| match err {
+                    reqwest_middleware::Error::Reqwest(err) => Error::ReqwestErr(err),
+                    reqwest_middleware::Error::Middleware(err) => Error::MiddlewareErr(err.into()),
+                })
+        })
+    }
+}
+
+/// Send the requests with `reqwest::blocking`, used by the [blocking](crate::blocking) client
+///
+/// The request is sent when the future is first polled, blocking the thread until the
+/// whole response is read.
+#[cfg(feature = "blocking")]
+#[derive(Debug, Clone)]
+pub(crate) struct BlockingTransport(pub reqwest::blocking::Client);
+
+#[cfg(feature = "blocking")]
+impl HttpTransport for BlockingTransport {
+    fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+        use reqwest::ResponseBuilderExt;
+
+        Box::pin(async move {
+            let mut blocking =
+                reqwest::blocking::Request::new(request.method().clone(), request.url().clone());
+            *blocking.headers_mut() = request.headers().clone();
+            *blocking.timeout_mut() = request.timeout().copied();
+            if let Some(body) = request.body().and_then(reqwest::Body::as_bytes) {
+                *blocking.body_mut() = Some(body.to_vec().into());
+            }
+
+            let response = self.0.execute(blocking)?;
+            let mut builder = http::Response::builder()
+                .status(response.status())
+                .version(response.version())
+                .url(response.url().clone());
+            for (name, value) in response.headers() {
+                builder = builder.header(name, value);
+            }
+            let body = response.bytes()?;
+
+            Ok(builder
+                .body(body)
+                .expect("The parts of a received response are valid")
+                .into())
+        })
+    }
+}
+
+/// A transport shared by the clones of the client
+pub(crate) type SharedTransport = Arc<dyn HttpTransport>;
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use futures_util::future::BoxFuture;
+    use reqwest::{Request, Response};
+
+    use super::HttpTransport;
+    use crate::{HelloAsso, Result};
+
+    /// Answer every request with the same body and record the requested urls
+    #[derive(Debug, Default)]
+    struct Canned {
+        body: &'static str,
+        urls: Mutex<Vec<String>>,
+    }
+
+    impl HttpTransport for Canned {
+        fn execute(&self, request: Request) -> BoxFuture<'_, Result<Response>> {
+            self.urls.lock().unwrap().push(request.url().to_string());
+            let response = http::Response::builder().body(self.body).unwrap();
+            Box::pin(async move { Ok(response.into()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn custom_transport() {
+        let transport = std::sync::Arc::new(Canned {
+            body: r#"{ "id": 42, "amount": 1250 }"#,
+            ..Default::default()
+        });
+        let client = HelloAsso::builder("abc".to_string(), "abc".to_string())
+            .with_transport(transport.clone())
+            .build();
+
+        let payment = client.get_payment(42).await.unwrap();
+
+        assert_eq!(payment.id, 42);
+        assert_eq!(
+            transport.urls.lock().unwrap().as_slice(),
+            ["https://api.helloasso-sandbox.com/v5/payments/42"]
+        );
+    }
+
+    #[cfg(feature = "reqwest-middleware")]
+    #[tokio::test]
+    async fn middleware_client() {
+        use reqwest_middleware::{ClientBuilder, Middleware, Next};
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use task_local_extensions::Extensions;
+
+        use crate::test_util::{MockApi, CLIENT_ID, CLIENT_SECRET, PAYMENT_ID};
+
+        static REQUESTS: AtomicUsize = AtomicUsize::new(0);
+
+        /// Count the requests and refuse the ones to `/v5/forbidden`
+        struct Counter;
+
+        #[async_trait::async_trait]
+        impl Middleware for Counter {
+            async fn handle(
+                &self,
+                request: Request,
+                extensions: &mut Extensions,
+                next: Next<'_>,
+            ) -> reqwest_middleware::Result<Response> {
+                REQUESTS.fetch_add(1, Ordering::SeqCst);
+                if request.url().path() == "/v5/forbidden" {
+                    return Err(reqwest_middleware::Error::Middleware(anyhow::anyhow!(
+                        "refused by the middleware"
+                    )));
+                }
+                next.run(request, extensions).await
+            }
+        }
+
+        let api = MockApi::start().await;
+        let client = HelloAsso::builder(CLIENT_ID.to_string(), CLIENT_SECRET.to_string())
+            .with_base_url(&api.uri())
+            .unwrap()
+            .with_middleware_client(
+                ClientBuilder::new(reqwest::Client::new())
+                    .with(Counter)
+                    .build(),
+            )
+            .get_token()
+            .await
+            .unwrap()
+            .config_client()
+            .unwrap()
+            .build();
+
+        client.get_payment(PAYMENT_ID).await.unwrap();
+        let refused = client.get_raw("forbidden", &[]).await;
+
+        assert_eq!(REQUESTS.load(Ordering::SeqCst), 3);
+        assert!(
+            matches!(refused, Err(crate::Error::MiddlewareErr(_))),
+            "{refused:?}"
+        );
+    }
+}
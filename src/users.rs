@@ -0,0 +1,172 @@
+//! `users` the organizations the authenticated client has access to
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decode::{is_strict, unknown_value, Name},
+    HelloAsso, Organization, Result,
+};
+
+/// An organization the client can act on, with its role
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuthorizedOrg {
+    pub slug: String,
+    pub name: String,
+    pub role: OrganizationRole,
+    /// Whether the role allows to modify the organization data
+    pub can_write: bool,
+}
+
+/// The role of the client on an organization
+///
+/// An unknown role is kept as [Other](crate::OrganizationRole::Other) and grants no write access.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(try_from = "Name", into = "String")]
+pub enum OrganizationRole {
+    OrganizationAdmin,
+    FormAdmin,
+    OrganizationReader,
+    Other(String),
+}
+
+impl OrganizationRole {
+    /// Whether this role allows to modify the organization data
+    pub fn can_write(&self) -> bool {
+        matches!(
+            self,
+            OrganizationRole::OrganizationAdmin | OrganizationRole::FormAdmin
+        )
+    }
+}
+
+impl From<String> for OrganizationRole {
+    fn from(role: String) -> Self {
+        match role.as_str() {
+            "OrganizationAdmin" => OrganizationRole::OrganizationAdmin,
+            "FormAdmin" => OrganizationRole::FormAdmin,
+            "OrganizationReader" => OrganizationRole::OrganizationReader,
+            _ => OrganizationRole::Other(role),
+        }
+    }
+}
+
+impl TryFrom<Name> for OrganizationRole {
+    type Error = String;
+
+    fn try_from(name: Name) -> std::result::Result<Self, String> {
+        match OrganizationRole::from(name.0) {
+            OrganizationRole::Other(role) if is_strict() => Err(unknown_value(&role)),
+            role => Ok(role),
+        }
+    }
+}
+
+impl From<OrganizationRole> for String {
+    fn from(role: OrganizationRole) -> Self {
+        match role {
+            OrganizationRole::OrganizationAdmin => "OrganizationAdmin".to_string(),
+            OrganizationRole::FormAdmin => "FormAdmin".to_string(),
+            OrganizationRole::OrganizationReader => "OrganizationReader".to_string(),
+            OrganizationRole::Other(role) => role,
+        }
+    }
+}
+
+/// An organization as returned by `users/me/organizations`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UserOrganization {
+    organization_slug: String,
+    name: String,
+    role: Option<OrganizationRole>,
+}
+
+impl From<UserOrganization> for AuthorizedOrg {
+    fn from(organization: UserOrganization) -> Self {
+        let role = organization
+            .role
+            .unwrap_or_else(|| OrganizationRole::Other(String::new()));
+
+        AuthorizedOrg {
+            slug: organization.organization_slug,
+            name: organization.name,
+            can_write: role.can_write(),
+            role,
+        }
+    }
+}
+
+impl HelloAsso {
+    /// Get the organizations the client has access to
+    ///
+    /// Use [get_authorized_organizations](crate::HelloAsso::get_authorized_organizations)
+    /// to know the role of the client on each of them.
+    pub async fn my_organizations(&self) -> Result<Vec<Organization>> {
+        let url = self.endpoint(&["users", "me", "organizations"]);
+        self.get(url, &()).await
+    }
+
+    /// Get the organizations the client can act on, with its role on each of them
+    pub async fn get_authorized_organizations(&self) -> Result<Vec<AuthorizedOrg>> {
+        let url = self.endpoint(&["users", "me", "organizations"]);
+        let organizations = self.get::<Vec<UserOrganization>, _>(url, &()).await?;
+
+        Ok(organizations.into_iter().map(AuthorizedOrg::from).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UserOrganization;
+    use crate::{
+        test_util::{self, MockApi, ORGANIZATION_SLUG},
+        AuthorizedOrg, OrganizationRole,
+    };
+    use serde_json::json;
+    use wiremock::{
+        matchers::{method, path},
+        Mock, ResponseTemplate,
+    };
+
+    #[test]
+    fn authorized_organizations() {
+        let organizations: Vec<UserOrganization> = serde_json::from_value(json!([
+            { "organizationSlug": "admin-asso", "name": "Admin asso", "role": "OrganizationAdmin" },
+            { "organizationSlug": "read-asso", "name": "Read asso", "role": "OrganizationReader" },
+            { "organizationSlug": "new-asso", "name": "New asso", "role": "Treasurer" }
+        ]))
+        .unwrap();
+
+        let organizations = organizations
+            .into_iter()
+            .map(AuthorizedOrg::from)
+            .collect::<Vec<_>>();
+
+        assert!(organizations[0].can_write);
+        assert!(!organizations[1].can_write);
+        assert_eq!(
+            organizations[2].role,
+            OrganizationRole::Other("Treasurer".to_string())
+        );
+        assert!(!organizations[2].can_write);
+    }
+
+    #[tokio::test]
+    async fn my_organizations() {
+        let api = MockApi::start().await;
+        Mock::given(method("GET"))
+            .and(path("/v5/users/me/organizations"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(json!([test_util::organization()])),
+            )
+            .with_priority(1)
+            .mount(api.server())
+            .await;
+        let client = api.client().await.unwrap();
+
+        let organizations = client.my_organizations().await.unwrap();
+
+        assert_eq!(organizations.len(), 1);
+        assert_eq!(organizations[0].organization_slug, ORGANIZATION_SLUG);
+    }
+}
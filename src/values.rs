@@ -0,0 +1,89 @@
+//! `values` the values accepted by the api, ie the categories used to search the directory
+//!
+//! ```rust,no_run
+//! # use helloasso::{values, DirectoryOrgQuery, HelloAsso, PaginationParams};
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> helloasso::Result<()> {
+//! # let client = HelloAsso::new("client_id".to_string(), "client_secret".to_string()).await?;
+//! let categories = values::organization_categories(&client).await?;
+//! let query = categories
+//!     .iter()
+//!     .fold(DirectoryOrgQuery::default(), |query, category| {
+//!         query.with_category(category.organization_category())
+//!     });
+//! let organizations = client
+//!     .directory_search_organizations(&query, &PaginationParams::default())
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{HelloAsso, OrganizationCategory, Result};
+
+/// A category of organization or of company
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Category {
+    pub id: u64,
+    pub label: String,
+}
+
+impl Category {
+    /// The category as used to search the directory
+    pub fn organization_category(&self) -> OrganizationCategory {
+        OrganizationCategory::from(self.label.clone())
+    }
+}
+
+/// A public tag, set by the organizations on their forms
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Tag {
+    pub id: Option<u64>,
+    pub name: String,
+}
+
+/// Get the categories of organizations
+pub async fn organization_categories(client: &HelloAsso) -> Result<Vec<Category>> {
+    let url = client.endpoint(&["values", "organization", "categories"]);
+    client.get(url, &()).await
+}
+
+/// Get the categories of companies
+pub async fn company_categories(client: &HelloAsso) -> Result<Vec<Category>> {
+    let url = client.endpoint(&["values", "company-categories"]);
+    client.get(url, &()).await
+}
+
+/// Get the public tags
+pub async fn tags(client: &HelloAsso) -> Result<Vec<Tag>> {
+    let url = client.endpoint(&["values", "tags"]);
+    client.get(url, &()).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Category;
+    use crate::OrganizationCategory;
+    use serde_json::json;
+
+    #[test]
+    fn category_to_organization_category() {
+        let categories: Vec<Category> = serde_json::from_value(json!([
+            { "id": 1, "label": "Sport" },
+            { "id": 42, "label": "Astronomy" }
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            categories[0].organization_category(),
+            OrganizationCategory::Sport
+        );
+        assert_eq!(
+            categories[1].organization_category(),
+            OrganizationCategory::Other("Astronomy".to_string())
+        );
+    }
+}
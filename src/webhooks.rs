@@ -0,0 +1,336 @@
+//! `webhooks` the notifications helloasso sends to the notification url
+//!
+//! Helloasso sends a notification again when the notification url doesn't
+//! answer with a success status, a [DedupStore](crate::webhooks::DedupStore)
+//! can be used to process each notification only once.
+//!
+//! ```rust
+//! use helloasso::webhooks::{should_process, InMemoryDedupStore, Notification};
+//! # let body = r#"{ "eventType": "Payment", "data": { "id": 42, "amount": 1250 } }"#;
+//!
+//! let store = InMemoryDedupStore::default();
+//! let notification = Notification::from_json(body).unwrap();
+//!
+//! if should_process(&notification, &store) {
+//!     // Process the notification
+//! }
+//! assert!(!should_process(&notification, &store));
+//! ```
+
+use std::{collections::HashSet, fmt::Display, sync::Mutex};
+
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::{
+    decode::{decode_json, is_strict, unknown_value, DecodeMode},
+    FormPublic, FormState, Order, Organization, Payment, PaymentState, Result,
+};
+
+#[cfg(feature = "actix")]
+pub mod actix;
+#[cfg(feature = "axum")]
+pub mod axum;
+
+/// A notification sent by helloasso
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    #[serde(flatten)]
+    pub event: NotificationEvent,
+    /// The metadata of the checkout intent, see [Metadata](crate::Metadata)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Value>,
+}
+
+/// The event of a [Notification](crate::webhooks::Notification) and its data
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "eventType", content = "data")]
+pub enum NotificationEvent {
+    Order(Order),
+    Payment(Payment),
+    Form(FormPublic),
+    Organization(Organization),
+    /// An event type the client doesn't know, kept as is
+    #[serde(untagged)]
+    Unknown {
+        #[serde(rename = "eventType")]
+        event_type: String,
+        data: Value,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RawNotification {
+    event_type: String,
+    #[serde(default)]
+    data: Value,
+    #[serde(default)]
+    metadata: Option<Value>,
+}
+
+impl<'de> Deserialize<'de> for Notification {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let raw = RawNotification::deserialize(deserializer)?;
+        let data = raw.data;
+
+        let event = match raw.event_type.as_str() {
+            "Order" => {
+                NotificationEvent::Order(Order::deserialize(data).map_err(D::Error::custom)?)
+            }
+            "Payment" => {
+                NotificationEvent::Payment(Payment::deserialize(data).map_err(D::Error::custom)?)
+            }
+            "Form" => {
+                NotificationEvent::Form(FormPublic::deserialize(data).map_err(D::Error::custom)?)
+            }
+            "Organization" => NotificationEvent::Organization(
+                Organization::deserialize(data).map_err(D::Error::custom)?,
+            ),
+            event_type if is_strict() => return Err(D::Error::custom(unknown_value(event_type))),
+            _ => NotificationEvent::Unknown {
+                event_type: raw.event_type,
+                data,
+            },
+        };
+
+        Ok(Notification {
+            event,
+            metadata: raw.metadata,
+        })
+    }
+}
+
+impl Notification {
+    /// Decode the body helloasso posts to the notification url
+    ///
+    /// An event type the client doesn't know is decoded as [Unknown](crate::webhooks::NotificationEvent::Unknown).
+    pub fn from_json(body: &str) -> Result<Self> {
+        Notification::from_slice(body.as_bytes())
+    }
+
+    /// Same as `from_json` but from the raw bytes of the body
+    pub fn from_slice(body: &[u8]) -> Result<Self> {
+        decode_json(body, DecodeMode::Lenient)
+    }
+
+    /// The identifier of the notification, the same for every delivery of a notification
+    ///
+    /// Helloasso sends several notifications for a resource, ie when a payment is
+    /// authorized then refunded, the id is keyed on the state of the resource to tell
+    /// them apart. It only depends on the values sent by helloasso, not on how the
+    /// body is formatted.
+    pub fn id(&self) -> NotificationId {
+        let (id, state) = match &self.event {
+            NotificationEvent::Order(order) => (order.id.to_string(), None),
+            NotificationEvent::Payment(payment) => (
+                payment.id.to_string(),
+                payment.state.as_ref().map(PaymentState::as_str),
+            ),
+            NotificationEvent::Form(form) => (
+                format!(
+                    "{}/{}/{}",
+                    form.organization_slug.as_deref().unwrap_or_default(),
+                    form.form_type,
+                    form.form_slug
+                ),
+                form.state.as_ref().map(FormState::as_str),
+            ),
+            NotificationEvent::Organization(organization) => {
+                (organization.organization_slug.clone(), None)
+            }
+            NotificationEvent::Unknown { data, .. } => {
+                let id = match data.get("id") {
+                    Some(Value::String(id)) => id.clone(),
+                    Some(id) => id.to_string(),
+                    None => String::new(),
+                };
+                (id, data.get("state").and_then(Value::as_str))
+            }
+        };
+
+        let mut notification_id = format!("{}:{}", self.event_type(), id);
+        if let Some(state) = state {
+            notification_id.push(':');
+            notification_id.push_str(state);
+        }
+        NotificationId(notification_id)
+    }
+
+    /// The type of the event, as sent by helloasso
+    pub fn event_type(&self) -> &str {
+        match &self.event {
+            NotificationEvent::Order(_) => "Order",
+            NotificationEvent::Payment(_) => "Payment",
+            NotificationEvent::Form(_) => "Form",
+            NotificationEvent::Organization(_) => "Organization",
+            NotificationEvent::Unknown { event_type, .. } => event_type,
+        }
+    }
+}
+
+/// Identifier of a [Notification](crate::webhooks::Notification), made of its event type, resource id and state
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct NotificationId(String);
+
+impl NotificationId {
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Display for NotificationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Storage of the notifications already processed
+pub trait DedupStore {
+    /// Whether the notification was already recorded
+    fn seen(&self, id: &NotificationId) -> bool;
+
+    /// Record a notification, return `false` if it was already recorded
+    fn record(&self, id: NotificationId) -> bool;
+}
+
+/// A [DedupStore](crate::webhooks::DedupStore) keeping the notifications in memory
+///
+/// The notifications are lost on restart, use a persistent store to survive them.
+#[derive(Debug, Default)]
+pub struct InMemoryDedupStore {
+    seen: Mutex<HashSet<NotificationId>>,
+}
+
+impl DedupStore for InMemoryDedupStore {
+    fn seen(&self, id: &NotificationId) -> bool {
+        self.seen
+            .lock()
+            .expect("Dedup store is never poisoned")
+            .contains(id)
+    }
+
+    fn record(&self, id: NotificationId) -> bool {
+        self.seen
+            .lock()
+            .expect("Dedup store is never poisoned")
+            .insert(id)
+    }
+}
+
+/// Record the notification and return whether it should be processed
+///
+/// Return `false` when the notification was already recorded, ie it is a redelivery.
+pub fn should_process(notification: &Notification, store: &impl DedupStore) -> bool {
+    store.record(notification.id())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{should_process, DedupStore, InMemoryDedupStore, Notification, NotificationEvent};
+    use crate::{Amount, Error};
+    use serde_json::json;
+
+    #[test]
+    fn notification_from_json() {
+        let notification = Notification::from_json(
+            r#"{
+                "eventType": "Payment",
+                "data": { "id": 42, "amount": 1250, "state": "Authorized" },
+                "metadata": { "userId": 7 }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(notification.metadata, Some(json!({ "userId": 7 })));
+        assert!(
+            matches!(notification.event, NotificationEvent::Payment(payment) if payment.amount == Amount::from_cents(1250))
+        );
+        assert!(matches!(
+            Notification::from_json(r#"{ "eventType": "Payment", "data": {} }"#),
+            Err(Error::DecodeErr(_))
+        ));
+    }
+
+    #[test]
+    fn unknown_notification() {
+        let body = json!({
+            "eventType": "Membership",
+            "data": { "id": 7, "name": "Gold" },
+            "metadata": { "userId": 7 }
+        });
+        let notification = Notification::from_json(&body.to_string()).unwrap();
+
+        assert_eq!(
+            notification.event,
+            NotificationEvent::Unknown {
+                event_type: "Membership".to_string(),
+                data: json!({ "id": 7, "name": "Gold" }),
+            }
+        );
+        assert_eq!(notification.event_type(), "Membership");
+        assert_eq!(notification.id().as_str(), "Membership:7");
+        assert_eq!(serde_json::to_value(&notification).unwrap(), body);
+    }
+
+    #[test]
+    fn notification_id() {
+        let notification: Notification = serde_json::from_value(json!({
+            "eventType": "Order",
+            "data": { "id": 12, "items": [] }
+        }))
+        .unwrap();
+
+        assert_eq!(notification.id().as_str(), "Order:12");
+        assert_eq!(notification.id(), notification.clone().id());
+    }
+
+    #[test]
+    fn notification_id_stable_for_raw_payload() {
+        let delivery = Notification::from_json(
+            r#"{ "eventType": "Payment", "data": { "id": 42, "amount": 1250, "state": "Authorized", "newField": true } }"#,
+        )
+        .unwrap();
+        let redelivery = Notification::from_json(
+            r#"{
+                "data": { "state": "Authorized", "newField": true, "amount": 1250, "id": 42 },
+                "eventType": "Payment"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(delivery.id().as_str(), "Payment:42:Authorized");
+        assert_eq!(delivery.id(), redelivery.id());
+    }
+
+    #[test]
+    fn notification_id_changes_with_data() {
+        let authorized: Notification = serde_json::from_value(json!({
+            "eventType": "Payment",
+            "data": { "id": 42, "amount": 1250, "state": "Authorized" }
+        }))
+        .unwrap();
+        let refunded: Notification = serde_json::from_value(json!({
+            "eventType": "Payment",
+            "data": { "id": 42, "amount": 1250, "state": "Refunded" }
+        }))
+        .unwrap();
+
+        assert_ne!(authorized.id(), refunded.id());
+    }
+
+    #[test]
+    fn process_once() {
+        let store = InMemoryDedupStore::default();
+        let notification: Notification = serde_json::from_value(json!({
+            "eventType": "Payment",
+            "data": { "id": 42, "amount": 1250 }
+        }))
+        .unwrap();
+
+        assert!(!store.seen(&notification.id()));
+        assert!(should_process(&notification, &store));
+        assert!(store.seen(&notification.id()));
+        assert!(!should_process(&notification, &store));
+    }
+}
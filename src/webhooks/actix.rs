@@ -0,0 +1,120 @@
+//! Receive the notifications with [actix web](https://docs.rs/actix-web), behind the `actix` feature
+//!
+//! ```rust,no_run
+//! use actix_web::App;
+//! use helloasso::webhooks::{actix::notification_route, Notification};
+//!
+//! let app = App::new().route(
+//!     "/helloasso/notifications",
+//!     notification_route(|notification: Notification| async move {
+//!         println!("received {}", notification.id());
+//!         Ok::<_, std::convert::Infallible>(())
+//!     }),
+//! );
+//! ```
+
+use std::{fmt::Display, future::Future, pin::Pin};
+
+use actix_web::{
+    dev::Payload, error::ErrorBadRequest, http::StatusCode, web, FromRequest, HttpRequest,
+    HttpResponse, Route,
+};
+
+use crate::{logging::log_error, webhooks::Notification};
+
+/// Extract a [Notification](crate::webhooks::Notification) from the body of a request
+///
+/// A body that isn't a notification is rejected with a `400 Bad Request`, a notification
+/// of an event type the client doesn't know is extracted as
+/// [Unknown](crate::webhooks::NotificationEvent::Unknown) so it is still acknowledged.
+impl FromRequest for Notification {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(request: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let body = web::Bytes::from_request(request, payload);
+
+        Box::pin(async move {
+            Notification::from_slice(&body.await?).map_err(|err| {
+                log_error!("Can't decode notification, {}", err);
+                ErrorBadRequest(err)
+            })
+        })
+    }
+}
+
+/// A `POST` route handing each notification to `callback`
+///
+/// The route answers `200 OK` when the callback succeeds. It answers
+/// `500 Internal Server Error` when it fails, so helloasso sends the notification again.
+pub fn notification_route<F, Fut, E>(callback: F) -> Route
+where
+    F: Fn(Notification) -> Fut + Clone + 'static,
+    Fut: Future<Output = Result<(), E>> + 'static,
+    E: Display,
+{
+    web::post().to(move |notification: Notification| {
+        let callback = callback.clone();
+        async move {
+            match callback(notification).await {
+                Ok(()) => HttpResponse::new(StatusCode::OK),
+                Err(err) => {
+                    log_error!("Can't process notification, {}", err);
+                    HttpResponse::new(StatusCode::INTERNAL_SERVER_ERROR)
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{http::StatusCode, test::TestRequest, FromRequest};
+
+    use crate::webhooks::{Notification, NotificationEvent};
+
+    #[tokio::test]
+    async fn extract_notification() {
+        let (request, mut payload) = TestRequest::post()
+            .set_payload(r#"{ "eventType": "Payment", "data": { "id": 42, "amount": 1250 } }"#)
+            .to_http_parts();
+
+        let notification = Notification::from_request(&request, &mut payload)
+            .await
+            .unwrap();
+
+        assert_eq!(notification.id().as_str(), "Payment:42");
+    }
+
+    #[tokio::test]
+    async fn extract_unknown_notification() {
+        let (request, mut payload) = TestRequest::post()
+            .set_payload(r#"{ "eventType": "Membership", "data": { "id": 7 } }"#)
+            .to_http_parts();
+
+        let notification = Notification::from_request(&request, &mut payload)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            notification.event,
+            NotificationEvent::Unknown { event_type, .. } if event_type == "Membership"
+        ));
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_notification() {
+        let (request, mut payload) = TestRequest::post()
+            .set_payload("not a notification")
+            .to_http_parts();
+
+        let rejection = Notification::from_request(&request, &mut payload)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            rejection.as_response_error().status_code(),
+            StatusCode::BAD_REQUEST
+        );
+    }
+}
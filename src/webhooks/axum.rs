@@ -0,0 +1,111 @@
+//! Receive the notifications with [axum](https://docs.rs/axum), behind the `axum` feature
+//!
+//! ```rust,no_run
+//! use axum::Router;
+//! use helloasso::webhooks::{axum::notification_route, Notification};
+//!
+//! let app: Router = Router::new().route(
+//!     "/helloasso/notifications",
+//!     notification_route(|notification: Notification| async move {
+//!         println!("received {}", notification.id());
+//!         Ok::<_, std::convert::Infallible>(())
+//!     }),
+//! );
+//! ```
+
+use std::{fmt::Display, future::Future};
+
+use axum::{
+    async_trait,
+    body::Bytes,
+    extract::{FromRequest, Request},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{post, MethodRouter},
+};
+
+use crate::{logging::log_error, webhooks::Notification};
+
+/// Extract a [Notification](crate::webhooks::Notification) from the body of a request
+///
+/// A body that isn't a notification is rejected with a `400 Bad Request`, a notification
+/// of an event type the client doesn't know is extracted as
+/// [Unknown](crate::webhooks::NotificationEvent::Unknown) so it is still acknowledged.
+#[async_trait]
+impl<S: Send + Sync> FromRequest<S> for Notification {
+    type Rejection = Response;
+
+    async fn from_request(request: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let body = Bytes::from_request(request, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        Notification::from_slice(&body).map_err(|err| {
+            log_error!("Can't decode notification, {}", err);
+            (StatusCode::BAD_REQUEST, err.to_string()).into_response()
+        })
+    }
+}
+
+/// A `POST` route handing each notification to `callback`
+///
+/// The route answers `200 OK` when the callback succeeds. It answers
+/// `500 Internal Server Error` when it fails, so helloasso sends the notification again.
+pub fn notification_route<S, F, Fut, E>(callback: F) -> MethodRouter<S>
+where
+    S: Clone + Send + Sync + 'static,
+    F: Fn(Notification) -> Fut + Clone + Send + Sync + 'static,
+    Fut: Future<Output = Result<(), E>> + Send + 'static,
+    E: Display,
+{
+    post(move |notification: Notification| async move {
+        match callback(notification).await {
+            Ok(()) => StatusCode::OK,
+            Err(err) => {
+                log_error!("Can't process notification, {}", err);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{body::Body, extract::FromRequest, extract::Request, http::StatusCode};
+
+    use crate::webhooks::{Notification, NotificationEvent};
+
+    #[tokio::test]
+    async fn extract_notification() {
+        let request = Request::new(Body::from(
+            r#"{ "eventType": "Payment", "data": { "id": 42, "amount": 1250 } }"#,
+        ));
+
+        let notification = Notification::from_request(request, &()).await.unwrap();
+
+        assert_eq!(notification.id().as_str(), "Payment:42");
+    }
+
+    #[tokio::test]
+    async fn extract_unknown_notification() {
+        let request = Request::new(Body::from(
+            r#"{ "eventType": "Membership", "data": { "id": 7 } }"#,
+        ));
+
+        let notification = Notification::from_request(request, &()).await.unwrap();
+
+        assert!(matches!(
+            notification.event,
+            NotificationEvent::Unknown { event_type, .. } if event_type == "Membership"
+        ));
+    }
+
+    #[tokio::test]
+    async fn reject_invalid_notification() {
+        let request = Request::new(Body::from("not a notification"));
+
+        let rejection = Notification::from_request(request, &()).await.unwrap_err();
+
+        assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+    }
+}